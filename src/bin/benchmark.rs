@@ -22,7 +22,7 @@ fn main() {
 
                 let start = Instant::now();
 
-                let result = eval(program, environment);
+                let result = eval(&program, environment);
 
                 let duration = start.elapsed().as_millis();
 
@@ -1,27 +1,35 @@
-use std::{
-    error::Error,
-    io::{self, Write},
-};
+use std::{env, error::Error, process};
 
 use monkey_rust::{
     compiler::Compiler,
+    object::Object,
     parser::{eprint_parse_errors, parse},
+    repl::{editor, History},
     vm::Vm,
 };
 
 const PROMPT: &str = ">> ";
+// Set to a file path to keep the input history between sessions.
+const HISTORY_VAR: &str = "MONKEY_HISTORY";
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut compiler = Compiler::new();
     let mut vm = Vm::new();
+    let mut history = match env::var_os(HISTORY_VAR) {
+        Some(path) => History::with_file(path)?,
+        None => History::new(),
+    };
 
     println!("Welcome to the Monkey programming language!");
     loop {
-        let mut input = String::new();
-
-        print!("{}", PROMPT);
-        io::stdout().flush()?;
-        io::stdin().read_line(&mut input)?;
+        let Some(input) =
+            editor::read_line(PROMPT, &mut history, |prefix| compiler.complete(prefix))?
+        else {
+            return Ok(());
+        };
+        if let Err(error) = history.push(&input) {
+            eprintln!("history error: {error}");
+        }
 
         match parse(&input) {
             Ok(program) => match compiler.compile(&program) {
@@ -29,7 +37,11 @@ fn main() -> Result<(), Box<dyn Error>> {
                     vm.update(bytecode);
                     match vm.run() {
                         Ok(_) => {
-                            println!("{}", vm.last_popped());
+                            let result = vm.last_popped();
+                            if let Object::Exit(code) = result.as_ref() {
+                                process::exit(*code);
+                            }
+                            println!("{}", result);
                         }
                         Err(error) => eprintln!("vm error: {error}"),
                     }
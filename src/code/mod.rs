@@ -35,6 +35,9 @@ pub enum Opcode {
     OpClosure,
     OpGetFree,
     OpCurrentClosure,
+    OpPow,
+    OpTry,
+    OpEndTry,
 }
 
 impl From<u8> for Opcode {
@@ -54,6 +57,12 @@ impl Instructions {
     }
 }
 
+impl Default for Instructions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl fmt::Debug for Instructions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let stream = &self.stream;
@@ -212,6 +221,18 @@ pub fn lookup(op: &Opcode) -> Definition {
             name: "OpGetFree",
             operand_widths: vec![],
         },
+        Opcode::OpPow => Definition {
+            name: "OpPow",
+            operand_widths: vec![],
+        },
+        Opcode::OpTry => Definition {
+            name: "OpTry",
+            operand_widths: vec![2],
+        },
+        Opcode::OpEndTry => Definition {
+            name: "OpEndTry",
+            operand_widths: vec![],
+        },
     }
 }
 
@@ -4,7 +4,7 @@ use crate::{
     code::{make, Instructions, Opcode},
     lexer::token::Token,
     object::{builtins::BUILTINS, CompiledFn, Object},
-    parser::ast::{BlockStatement, Expression, Node, Statement},
+    parser::ast::{BlockStatement, CallExpression, Expression, MatchExpression, Node, Statement},
 };
 
 use self::symbol::{Symbol, SymbolScope, SymbolTable};
@@ -31,6 +31,12 @@ impl CompilationScope {
     }
 }
 
+impl Default for CompilationScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct EmittedInstruction {
     pub opcode: Opcode,
@@ -46,6 +52,12 @@ impl EmittedInstruction {
     }
 }
 
+impl Default for EmittedInstruction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct Compiler {
     constants: Vec<Rc<Object>>,
     symbol_table: SymbolTable,
@@ -59,8 +71,8 @@ impl Compiler {
         let main_scope = CompilationScope::new();
 
         let mut symbol_table = SymbolTable::new();
-        for (i, (name, _)) in BUILTINS.iter().enumerate() {
-            symbol_table.define_builtin(i, name);
+        for (i, builtin) in BUILTINS.iter().enumerate() {
+            symbol_table.define_builtin(i, builtin.name);
         }
 
         Self {
@@ -72,7 +84,11 @@ impl Compiler {
         }
     }
 
-    pub fn compile(&mut self, node: &Node) -> Result<Bytecode, CompileError> {
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        self.symbol_table.complete(prefix)
+    }
+
+    pub fn compile(&mut self, node: &Node) -> Result<Bytecode<'_>, CompileError> {
         match node {
             Node::Program(prgm) => {
                 for stmt in prgm.0.iter() {
@@ -97,20 +113,30 @@ impl Compiler {
         Ok(())
     }
 
+    // Leaves the value of the block's last expression statement on the stack, or null.
+    fn compile_block_value(&mut self, block: &BlockStatement) -> Result<(), CompileError> {
+        self.compile_block_statement(block)?;
+        if !block.statements.is_empty() && self.last_instruction_is(Opcode::OpPop) {
+            self.remove_last_instr();
+        } else {
+            self.emit(Opcode::OpNull, &[]);
+        }
+
+        Ok(())
+    }
+
     fn compile_statement(&mut self, stmt: &Statement) -> Result<(), CompileError> {
         match stmt {
             Statement::Let(stmt) => {
-                let symbol = self.symbol_table.define(&stmt.identifier.name);
+                let symbol = self.define_symbol(&stmt.identifier.name, false)?;
                 self.compile_expression(&stmt.value)?;
-
-                self.emit(
-                    match &symbol.scope {
-                        SymbolScope::Global => Opcode::OpSetGlobal,
-                        SymbolScope::Local => Opcode::OpSetLocal,
-                        _ => unreachable!(),
-                    },
-                    &[symbol.index as i32],
-                );
+                self.emit_set_symbol(&symbol);
+                Ok(())
+            }
+            Statement::Const(stmt) => {
+                let symbol = self.define_symbol(&stmt.identifier.name, true)?;
+                self.compile_expression(&stmt.value)?;
+                self.emit_set_symbol(&symbol);
                 Ok(())
             }
             Statement::Return(stmt) => {
@@ -123,6 +149,11 @@ impl Compiler {
                 self.emit(Opcode::OpPop, &[]);
                 Ok(())
             }
+            Statement::Block(block) => {
+                self.compile_block_value(block)?;
+                self.emit(Opcode::OpPop, &[]);
+                Ok(())
+            }
         }
     }
 
@@ -141,6 +172,12 @@ impl Compiler {
                 self.emit(Opcode::OpConstant, &[int_pos]);
                 Ok(())
             }
+            Expression::Float(expr) => {
+                let float_obj = Object::Float(expr.value);
+                let float_pos = self.add_constant(float_obj) as i32;
+                self.emit(Opcode::OpConstant, &[float_pos]);
+                Ok(())
+            }
             Expression::String(expr) => {
                 let string_obj = Object::String(expr.value.to_string());
                 let string_pos = self.add_constant(string_obj) as i32;
@@ -173,6 +210,7 @@ impl Compiler {
                     Token::Minus => self.emit(Opcode::OpSub, &[]),
                     Token::Asterisk => self.emit(Opcode::OpMul, &[]),
                     Token::Slash => self.emit(Opcode::OpDiv, &[]),
+                    Token::Power => self.emit(Opcode::OpPow, &[]),
                     Token::GreaterThan => self.emit(Opcode::OpGreaterThan, &[]),
                     Token::Equal => self.emit(Opcode::OpEqual, &[]),
                     Token::NotEqual => self.emit(Opcode::OpNotEqual, &[]),
@@ -225,6 +263,66 @@ impl Compiler {
 
                 Ok(())
             }
+            Expression::Do(expr) => self.compile_block_value(&expr.body),
+            Expression::Try(expr) => {
+                // The VM jumps to the handler with the error message on the stack if the body
+                // fails before reaching OpEndTry.
+                let try_pos = self.emit(Opcode::OpTry, &[-1]);
+                self.compile_block_value(&expr.body)?;
+                self.emit(Opcode::OpEndTry, &[]);
+                let jump_pos = self.emit(Opcode::OpJump, &[-1]);
+
+                let handler_pos = self.current_instructions().stream.len();
+                self.update_operand(try_pos, handler_pos as i32);
+
+                let name = &expr.error.name;
+                let shadowed = self.symbol_table.get(name);
+                let error = self.symbol_table.define(name);
+                self.emit_set_symbol(&error);
+                self.compile_block_value(&expr.handler)?;
+                self.symbol_table.restore(name, shadowed);
+
+                let after_handler_pos = self.current_instructions().stream.len();
+                self.update_operand(jump_pos, after_handler_pos as i32);
+                Ok(())
+            }
+            Expression::Comparison(expr) => {
+                // Each operand is evaluated once, so it is kept in a slot to be the left-hand side
+                // of the next comparison. The chain stops at the first one that fails.
+                let lhs = self.define_temp("comparison lhs");
+                let rhs = self.define_temp("comparison rhs");
+                self.compile_expression(&expr.operands[0])?;
+                self.emit_set_symbol(&lhs);
+
+                let mut jump_cond_positions = Vec::new();
+                for (i, (operator, operand)) in
+                    expr.operators.iter().zip(&expr.operands[1..]).enumerate()
+                {
+                    if i > 0 {
+                        self.load_symbol(&rhs);
+                        self.emit_set_symbol(&lhs);
+                    }
+                    self.compile_expression(operand)?;
+                    self.emit_set_symbol(&rhs);
+                    self.compile_comparison(operator, &lhs, &rhs)?;
+                    if i < expr.operators.len() - 1 {
+                        jump_cond_positions.push(self.emit(Opcode::OpJumpCond, &[-1]));
+                    }
+                }
+
+                let jump_pos = self.emit(Opcode::OpJump, &[-1]);
+                let false_pos = self.current_instructions().stream.len();
+                for pos in jump_cond_positions {
+                    self.update_operand(pos, false_pos as i32);
+                }
+                self.emit(Opcode::OpFalse, &[]);
+
+                let after_false_pos = self.current_instructions().stream.len();
+                self.update_operand(jump_pos, after_false_pos as i32);
+                Ok(())
+            }
+            Expression::Match(expr) => self.compile_match_expression(expr),
+            Expression::Placeholder => Err("placeholder outside of call arguments".to_string()),
             Expression::ArrayLiteral(expr) => {
                 for el in expr.elements.iter() {
                     self.compile_expression(el)?;
@@ -267,26 +365,12 @@ impl Compiler {
                     self.emit(Opcode::OpReturn, &[]);
                 }
 
-                let free_symbols = self.symbol_table.free_symbols.to_vec();
-                let num_locals = self.symbol_table.num_definitions;
-                let instrs = self.leave_scope();
-
-                for symbol in &free_symbols {
-                    self.load_symbol(symbol);
-                }
-
-                let compiled_fn_obj = Object::CompiledFn(CompiledFn {
-                    instructions: instrs,
-                    num_locals,
-                    num_parameters: expr.parameters.len(),
-                });
-                let compiled_fn_pos = self.add_constant(compiled_fn_obj);
-                self.emit(
-                    Opcode::OpClosure,
-                    &[compiled_fn_pos as i32, free_symbols.len() as i32],
-                );
+                self.emit_closure(expr.parameters.len());
                 Ok(())
             }
+            Expression::Call(expr) if expr.arguments.contains(&Expression::Placeholder) => {
+                self.compile_partial_application(expr)
+            }
             Expression::Call(expr) => {
                 self.compile_expression(&expr.function)?;
 
@@ -300,7 +384,161 @@ impl Compiler {
         }
     }
 
-    fn bytecode(&self) -> Bytecode {
+    // Arms are tried in order, each jumping to the next when its value or guard doesn't match.
+    // Values are compared structurally, the way `deep_equal` does.
+    fn compile_match_expression(&mut self, expr: &MatchExpression) -> Result<(), CompileError> {
+        let subject = self.define_temp("match subject");
+        self.compile_expression(&expr.subject)?;
+        self.emit_set_symbol(&subject);
+
+        let mut end_jump_positions = Vec::new();
+        for arm in expr.arms.iter() {
+            let mut next_jump_positions = Vec::new();
+
+            // A bare identifier binds the subject for the guard and body only.
+            let binding = match &arm.value {
+                Expression::Identifier(identifier) => {
+                    let shadowed = self.symbol_table.get(&identifier.name);
+                    let symbol = self.symbol_table.define(&identifier.name);
+                    self.load_symbol(&subject);
+                    self.emit_set_symbol(&symbol);
+                    Some((&identifier.name, shadowed))
+                }
+                value => {
+                    let deep_equal = BUILTINS
+                        .iter()
+                        .position(|builtin| builtin.name == "deep_equal")
+                        .expect("deep_equal is a builtin");
+                    self.emit(Opcode::OpGetBuiltin, &[deep_equal as i32]);
+                    self.compile_expression(value)?;
+                    self.load_symbol(&subject);
+                    self.emit(Opcode::OpCall, &[2]);
+                    next_jump_positions.push(self.emit(Opcode::OpJumpCond, &[-1]));
+                    None
+                }
+            };
+            if let Some(guard) = &arm.guard {
+                self.compile_expression(guard)?;
+                next_jump_positions.push(self.emit(Opcode::OpJumpCond, &[-1]));
+            }
+            self.compile_expression(&arm.body)?;
+            end_jump_positions.push(self.emit(Opcode::OpJump, &[-1]));
+
+            if let Some((name, shadowed)) = binding {
+                self.symbol_table.restore(name, shadowed);
+            }
+            let next_arm_pos = self.current_instructions().stream.len();
+            for pos in next_jump_positions {
+                self.update_operand(pos, next_arm_pos as i32);
+            }
+        }
+
+        match &expr.default {
+            Some(default) => self.compile_expression(default)?,
+            None => {
+                self.emit(Opcode::OpNull, &[]);
+            }
+        }
+
+        let end_pos = self.current_instructions().stream.len();
+        for pos in end_jump_positions {
+            self.update_operand(pos, end_pos as i32);
+        }
+        Ok(())
+    }
+
+    // Like the evaluator, the function and the fixed arguments are evaluated once, up front. The
+    // result is a closure over them with one parameter per placeholder.
+    fn compile_partial_application(&mut self, expr: &CallExpression) -> Result<(), CompileError> {
+        let function = self.define_temp("partial function");
+        self.compile_expression(&expr.function)?;
+        self.emit_set_symbol(&function);
+
+        // The slot holding each fixed argument, or `None` for a placeholder.
+        let mut slots = Vec::new();
+        for arg in expr.arguments.iter() {
+            slots.push(match arg {
+                Expression::Placeholder => None,
+                arg => {
+                    let symbol = self.define_temp("partial argument");
+                    self.compile_expression(arg)?;
+                    self.emit_set_symbol(&symbol);
+                    Some(symbol)
+                }
+            });
+        }
+
+        self.enter_scope();
+        let mut num_parameters = 0;
+        let mut arguments = Vec::new();
+        for slot in slots.iter() {
+            arguments.push(match slot {
+                Some(symbol) => self.resolve_temp(symbol),
+                None => {
+                    num_parameters += 1;
+                    self.define_temp("placeholder")
+                }
+            });
+        }
+        let function = self.resolve_temp(&function);
+        self.load_symbol(&function);
+        for arg in arguments.iter() {
+            self.load_symbol(arg);
+        }
+        self.emit(Opcode::OpCall, &[arguments.len() as i32]);
+        self.emit(Opcode::OpReturnValue, &[]);
+
+        self.emit_closure(num_parameters);
+        Ok(())
+    }
+
+    // Leaves the function scope being compiled and pushes a closure over its free variables.
+    fn emit_closure(&mut self, num_parameters: usize) {
+        let free_symbols = self.symbol_table.free_symbols.to_vec();
+        let num_locals = self.symbol_table.num_definitions;
+        let instrs = self.leave_scope();
+
+        for symbol in &free_symbols {
+            self.load_symbol(symbol);
+        }
+
+        let compiled_fn_obj = Object::CompiledFn(CompiledFn {
+            instructions: instrs,
+            num_locals,
+            num_parameters,
+        });
+        let compiled_fn_pos = self.add_constant(compiled_fn_obj);
+        self.emit(
+            Opcode::OpClosure,
+            &[compiled_fn_pos as i32, free_symbols.len() as i32],
+        );
+    }
+
+    fn compile_comparison(
+        &mut self,
+        operator: &Token,
+        lhs: &Symbol,
+        rhs: &Symbol,
+    ) -> Result<(), CompileError> {
+        if *operator == Token::LessThan {
+            self.load_symbol(rhs);
+            self.load_symbol(lhs);
+            self.emit(Opcode::OpGreaterThan, &[]);
+            return Ok(());
+        }
+
+        self.load_symbol(lhs);
+        self.load_symbol(rhs);
+        match operator {
+            Token::GreaterThan => self.emit(Opcode::OpGreaterThan, &[]),
+            Token::Equal => self.emit(Opcode::OpEqual, &[]),
+            Token::NotEqual => self.emit(Opcode::OpNotEqual, &[]),
+            _ => return Err(format!("unknown operator: {}", operator.get_literal())),
+        };
+        Ok(())
+    }
+
+    fn bytecode(&self) -> Bytecode<'_> {
         Bytecode {
             instructions: self.current_instructions(),
             constants: &self.constants,
@@ -399,6 +637,42 @@ impl Compiler {
         instrs
     }
 
+    fn define_symbol(&mut self, name: &str, constant: bool) -> Result<Rc<Symbol>, CompileError> {
+        if self.symbol_table.is_constant(name) {
+            return Err(format!("cannot reassign constant: {}", name));
+        }
+
+        Ok(match constant {
+            true => self.symbol_table.define_constant(name),
+            false => self.symbol_table.define(name),
+        })
+    }
+
+    // A slot for an intermediate value. Its name is not a valid identifier, so programs can't
+    // refer to it, and is unique in its scope so nested temps don't shadow each other.
+    fn define_temp(&mut self, purpose: &str) -> Rc<Symbol> {
+        let name = format!("<{} {}>", purpose, self.symbol_table.num_definitions);
+        self.symbol_table.define(&name)
+    }
+
+    // Looks up a temp from an enclosing scope, capturing it if needed.
+    fn resolve_temp(&mut self, symbol: &Symbol) -> Rc<Symbol> {
+        self.symbol_table
+            .resolve(&symbol.name)
+            .expect("temps are defined before the scopes that use them")
+    }
+
+    fn emit_set_symbol(&mut self, symbol: &Symbol) {
+        self.emit(
+            match &symbol.scope {
+                SymbolScope::Global => Opcode::OpSetGlobal,
+                SymbolScope::Local => Opcode::OpSetLocal,
+                _ => unreachable!(),
+            },
+            &[symbol.index as i32],
+        );
+    }
+
     fn load_symbol(&mut self, symbol: &Symbol) {
         match symbol.scope {
             SymbolScope::Global => self.emit(Opcode::OpGetGlobal, &[symbol.index as i32]),
@@ -410,6 +684,12 @@ impl Compiler {
     }
 }
 
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct Bytecode<'a> {
     pub instructions: &'a Instructions,
     pub constants: &'a [Rc<Object>],
@@ -1,4 +1,8 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap, HashSet},
+    rc::Rc,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum SymbolScope {
@@ -20,6 +24,7 @@ pub struct Symbol {
 pub struct SymbolTable {
     pub outer: Option<Rc<RefCell<SymbolTable>>>,
     store: HashMap<String, Rc<Symbol>>,
+    constants: HashSet<String>,
     pub num_definitions: usize,
     pub free_symbols: Vec<Rc<Symbol>>,
 }
@@ -29,6 +34,7 @@ impl SymbolTable {
         Self {
             outer: None,
             store: HashMap::new(),
+            constants: HashSet::new(),
             num_definitions: 0,
             free_symbols: Vec::new(),
         }
@@ -38,6 +44,7 @@ impl SymbolTable {
         Self {
             outer: Some(Rc::new(RefCell::new(outer))),
             store: HashMap::new(),
+            constants: HashSet::new(),
             num_definitions: 0,
             free_symbols: Vec::new(),
         }
@@ -60,6 +67,15 @@ impl SymbolTable {
         symbol
     }
 
+    pub fn define_constant(&mut self, name: &str) -> Rc<Symbol> {
+        self.constants.insert(name.to_string());
+        self.define(name)
+    }
+
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains(name)
+    }
+
     pub fn define_builtin(&mut self, index: usize, name: &str) -> Rc<Symbol> {
         let symbol = Rc::new(Symbol {
             name: name.to_string(),
@@ -93,6 +109,35 @@ impl SymbolTable {
         symbol
     }
 
+    // The symbol `name` refers to in this table, without looking in enclosing ones.
+    pub fn get(&self, name: &str) -> Option<Rc<Symbol>> {
+        self.store.get(name).cloned()
+    }
+
+    // Ends a binding that only lasts for a block, putting back the symbol it shadowed.
+    pub fn restore(&mut self, name: &str, shadowed: Option<Rc<Symbol>>) {
+        match shadowed {
+            Some(symbol) => self.store.insert(name.to_string(), symbol),
+            None => self.store.remove(name),
+        };
+    }
+
+    // Candidates for completing `prefix` in the REPL. Builtins are defined in the global table,
+    // so they are included.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let mut names = match &self.outer {
+            Some(outer) => outer.borrow().complete(prefix).into_iter().collect(),
+            None => BTreeSet::new(),
+        };
+        names.extend(
+            self.store
+                .keys()
+                .filter(|name| name.starts_with(prefix))
+                .cloned(),
+        );
+        names.into_iter().collect()
+    }
+
     pub fn resolve(&mut self, name: &str) -> Option<Rc<Symbol>> {
         match self.store.get(name) {
             Some(symbol) => Some(symbol).cloned(),
@@ -112,3 +157,9 @@ impl SymbolTable {
         }
     }
 }
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
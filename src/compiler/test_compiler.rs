@@ -940,4 +940,21 @@ mod tests {
             test_compiling(input, &expected_constants[i], expected_instrs[i].clone());
         }
     }
+
+    #[test]
+    fn test_try_expressions() {
+        test_compiling(
+            "try { 1 } catch (e) { e }",
+            &[Object::Integer(1)],
+            vec![
+                make(Opcode::OpTry, &[10]),
+                make(Opcode::OpConstant, &[0]),
+                make(Opcode::OpEndTry, &[]),
+                make(Opcode::OpJump, &[16]),
+                make(Opcode::OpSetGlobal, &[0]),
+                make(Opcode::OpGetGlobal, &[0]),
+                make(Opcode::OpPop, &[]),
+            ],
+        );
+    }
 }
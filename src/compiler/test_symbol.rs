@@ -428,4 +428,36 @@ mod tests {
 
         assert_eq!(expected, *actual);
     }
+
+    #[test]
+    fn test_complete() {
+        let mut global = SymbolTable::new();
+        global.define_builtin(0, "len");
+        global.define("length");
+        global.define("x");
+        let mut local = SymbolTable::new_enclosed(global);
+        local.define("left");
+
+        assert_eq!(local.complete("le"), vec!["left", "len", "length"]);
+        assert_eq!(local.complete("x"), vec!["x"]);
+        assert!(local.complete("y").is_empty());
+    }
+
+    #[test]
+    fn test_restore_shadowed() {
+        let mut global = SymbolTable::new();
+        let a = global.define("a");
+        let mut local = SymbolTable::new_enclosed(global);
+        let b = local.define("b");
+
+        let shadowed = local.get("a");
+        local.define("a");
+        local.restore("a", shadowed);
+        assert_eq!(local.resolve("a"), Some(a));
+
+        let shadowed = local.get("b");
+        local.define("b");
+        local.restore("b", shadowed);
+        assert_eq!(local.resolve("b"), Some(b));
+    }
 }
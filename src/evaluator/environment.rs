@@ -1,10 +1,27 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    rc::Rc,
+};
 
-use crate::object::Object;
+use crate::object::{builtins::builtin_names, Object};
+
+type EnvError = String;
+
+// Bindings of a single environment as captured by `Environment::snapshot`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnvSnapshot {
+    map: HashMap<String, Rc<Object>>,
+    constants: HashSet<String>,
+    protected: HashSet<String>,
+}
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Environment {
     map: HashMap<String, Rc<Object>>,
+    constants: HashSet<String>,
+    protected: HashSet<String>,
+    sandboxed: bool,
     outer: Option<Rc<RefCell<Environment>>>,
 }
 
@@ -12,17 +29,26 @@ impl Environment {
     pub fn new() -> Self {
         Environment {
             map: HashMap::new(),
+            constants: HashSet::new(),
+            protected: HashSet::new(),
+            sandboxed: false,
             outer: None,
         }
     }
 
-    pub fn new_enclosed(self) -> Self {
+    pub fn new_sandboxed() -> Self {
         let mut env = Environment::new();
-        env.outer = Some(Rc::new(RefCell::new(self)));
+        env.sandboxed = true;
         env
     }
 
-    pub fn get(&self, identifier: &String) -> Option<Rc<Object>> {
+    pub fn new_enclosed(outer: Rc<RefCell<Environment>>) -> Self {
+        let mut env = Environment::new();
+        env.outer = Some(outer);
+        env
+    }
+
+    pub fn get(&self, identifier: &str) -> Option<Rc<Object>> {
         match self.map.get(identifier) {
             None => match &self.outer {
                 Some(outer) => outer.borrow().get(identifier),
@@ -32,7 +58,111 @@ impl Environment {
         }
     }
 
-    pub fn set(&mut self, identifier: String, val: Rc<Object>) {
+    // Lends the bound value to `f` instead of handing out another reference to it.
+    pub fn get_ref<T>(&self, identifier: &str, f: impl FnOnce(&Object) -> T) -> Option<T> {
+        match self.map.get(identifier) {
+            None => match &self.outer {
+                Some(outer) => outer.borrow().get_ref(identifier, f),
+                None => None,
+            },
+            Some(val) => Some(f(val)),
+        }
+    }
+
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandboxed
+            || match &self.outer {
+                Some(outer) => outer.borrow().is_sandboxed(),
+                None => false,
+            }
+    }
+
+    pub fn is_protected(&self, identifier: &str) -> bool {
+        self.protected.contains(identifier)
+            || match &self.outer {
+                Some(outer) => outer.borrow().is_protected(identifier),
+                None => false,
+            }
+    }
+
+    pub fn set(&mut self, identifier: String, val: Rc<Object>) -> Result<(), EnvError> {
+        if self.constants.contains(&identifier) {
+            return Err(format!("cannot reassign constant: {}", identifier));
+        }
+        if self.is_protected(&identifier) {
+            return Err(format!("cannot reassign protected name: {}", identifier));
+        }
+
         self.map.insert(identifier, val);
+        Ok(())
+    }
+
+    pub fn set_const(&mut self, identifier: String, val: Rc<Object>) -> Result<(), EnvError> {
+        self.set(identifier.clone(), val)?;
+        self.constants.insert(identifier);
+        Ok(())
+    }
+
+    pub fn set_protected(&mut self, identifier: String, val: Rc<Object>) -> Result<(), EnvError> {
+        self.set(identifier.clone(), val)?;
+        self.protected.insert(identifier);
+        Ok(())
+    }
+
+    pub fn local_bindings(&self) -> BTreeMap<String, Rc<Object>> {
+        self.map
+            .iter()
+            .map(|(name, val)| (name.clone(), val.clone()))
+            .collect()
+    }
+
+    // Bindings of the outermost environment in the chain.
+    pub fn global_bindings(&self) -> BTreeMap<String, Rc<Object>> {
+        match &self.outer {
+            Some(outer) => outer.borrow().global_bindings(),
+            None => self.local_bindings(),
+        }
+    }
+
+    // Names bound here or in any outer environment.
+    pub fn names(&self) -> BTreeSet<String> {
+        let mut names = match &self.outer {
+            Some(outer) => outer.borrow().names(),
+            None => BTreeSet::new(),
+        };
+        names.extend(self.map.keys().cloned());
+        names
+    }
+
+    // Candidates for completing `prefix` in the REPL, drawn from bound names and builtins.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let mut candidates = self.names();
+        candidates.extend(builtin_names().into_iter().map(String::from));
+        candidates
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .collect()
+    }
+
+    // Only this environment's own bindings are captured; outer environments are shared and left
+    // untouched by `restore`.
+    pub fn snapshot(&self) -> EnvSnapshot {
+        EnvSnapshot {
+            map: self.map.clone(),
+            constants: self.constants.clone(),
+            protected: self.protected.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: EnvSnapshot) {
+        self.map = snapshot.map;
+        self.constants = snapshot.constants;
+        self.protected = snapshot.protected;
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
     }
 }
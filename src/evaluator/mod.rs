@@ -1,38 +1,168 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::BTreeMap;
 use std::rc::Rc;
+use std::slice;
+use std::time::{Duration, Instant};
 
-use crate::object::builtins::get_builtin_fn;
-use crate::object::{get_bool_object, is_truthy, HashKey, HashPair, Object};
+use crate::object::builtins::{get_builtin_fn, SANDBOX_DISABLED_BUILTINS};
+use crate::object::{
+    arrays_equal, get_bool_object, integer_pow, is_truthy, range_get, HashKey, HashPair, HashPairs,
+    Integer, Object,
+};
 use crate::parser::ast::{
-    BlockStatement, Expression, HashLiteralExpression, IdentifierExpression, IfExpression, Node,
-    Statement,
+    BlockStatement, CallExpression, ComparisonExpression, Expression, ExpressionStatement,
+    HashLiteralExpression, IdentifierExpression, IfExpression, IndexExpression, InfixExpression,
+    LetStatement, MatchExpression, Node, PrefixExpression, Program, Statement, TryExpression,
 };
+use crate::parser::parse_program;
+use crate::vm::run_closure;
 use environment::Environment;
 
 pub mod environment;
+pub mod prelude;
+mod test_environment;
 mod test_evaluator;
 
-type EvalError = String;
+// `exit` travels the error path so that nothing after it runs, but only `Error` can be caught.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum EvalError {
+    Error(String),
+    Exit(i32),
+}
+
+impl From<String> for EvalError {
+    fn from(message: String) -> Self {
+        EvalError::Error(message)
+    }
+}
+
+impl From<EvalError> for Object {
+    fn from(err: EvalError) -> Self {
+        match err {
+            EvalError::Error(message) => Object::Error(message),
+            EvalError::Exit(code) => Object::Exit(code),
+        }
+    }
+}
+
+const DEADLINE_CHECK_INTERVAL: usize = 1000;
+const TIMEOUT_ERROR: &str = "evaluation timed out";
+
+thread_local! {
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+    static EVAL_COUNT: Cell<usize> = const { Cell::new(0) };
+    static ERROR_CONTEXT: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// The functions the last uncaught error propagated out of, outermost first. This is kept out of
+// the error message so that `try` and callers matching on messages see only what was raised.
+pub fn error_context() -> Vec<String> {
+    ERROR_CONTEXT.with(|context| context.borrow().iter().rev().cloned().collect())
+}
 
-pub fn eval(node: Node, env: Rc<RefCell<Environment>>) -> Rc<Object> {
+fn clear_error_context() {
+    ERROR_CONTEXT.with(|context| context.borrow_mut().clear());
+}
+
+fn push_error_context(name: &str) {
+    ERROR_CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        // Recursive calls are reported once rather than once per frame.
+        if context.last().map(String::as_str) != Some(name) {
+            context.push(name.to_string());
+        }
+    });
+}
+
+pub fn eval(node: &Node, env: Rc<RefCell<Environment>>) -> Rc<Object> {
+    clear_error_context();
     match node {
-        Node::Program(prgm) => match eval_program(&prgm.0, env) {
+        Node::Program(prgm) => eval_program(prgm, env),
+        Node::Statement(stmt) => match eval_statement(stmt, env) {
             Ok(evaluated) => evaluated,
-            Err(err) => Rc::new(Object::Error(err)),
+            Err(err) => Rc::new(err.into()),
         },
-        Node::Statement(stmt) => match eval_statement(&stmt, env) {
+        Node::Expression(expr) => match eval_expression(expr, env) {
             Ok(evaluated) => evaluated,
-            Err(err) => Rc::new(Object::Error(err)),
-        },
-        Node::Expression(expr) => match eval_expression(&expr, env) {
-            Ok(evaluated) => evaluated,
-            Err(err) => Rc::new(Object::Error(err)),
+            Err(err) => Rc::new(err.into()),
         },
     }
 }
 
-fn eval_program(
+pub fn eval_program(program: &Program, env: Rc<RefCell<Environment>>) -> Rc<Object> {
+    clear_error_context();
+    match eval_statements(&program.0, env) {
+        Ok(evaluated) => evaluated,
+        Err(err) => Rc::new(err.into()),
+    }
+}
+
+pub fn run(input: &str) -> Rc<Object> {
+    match parse_program(input) {
+        Ok(program) => eval_program(&program, Rc::new(RefCell::new(Environment::new()))),
+        Err(errs) => Rc::new(Object::Error(errs.join("; "))),
+    }
+}
+
+pub fn eval_each(program: &Program, env: Rc<RefCell<Environment>>) -> Vec<Rc<Object>> {
+    let mut results = Vec::new();
+    clear_error_context();
+
+    for stmt in program.0.iter() {
+        match eval_statement(stmt, env.clone()) {
+            Ok(result) => match result.as_ref() {
+                Object::ReturnValue(value) => {
+                    results.push(value.clone());
+                    break;
+                }
+                Object::Error(_) => {
+                    results.push(result);
+                    break;
+                }
+                _ => results.push(result),
+            },
+            Err(err) => {
+                results.push(Rc::new(err.into()));
+                break;
+            }
+        }
+    }
+    results
+}
+
+pub fn eval_with_timeout(
+    node: &Node,
+    env: Rc<RefCell<Environment>>,
+    timeout: Duration,
+) -> Rc<Object> {
+    let previous = DEADLINE.with(|deadline| deadline.replace(Some(Instant::now() + timeout)));
+    let result = eval(node, env);
+    DEADLINE.with(|deadline| deadline.set(previous));
+    result
+}
+
+fn check_deadline() -> Result<(), EvalError> {
+    if DEADLINE.with(|deadline| deadline.get()).is_none() {
+        return Ok(());
+    }
+
+    let count = EVAL_COUNT.with(|count| {
+        count.set(count.get().wrapping_add(1));
+        count.get()
+    });
+    if count.is_multiple_of(DEADLINE_CHECK_INTERVAL) && deadline_passed() {
+        return Err(TIMEOUT_ERROR.to_string().into());
+    }
+    Ok(())
+}
+
+fn deadline_passed() -> bool {
+    DEADLINE
+        .with(|deadline| deadline.get())
+        .is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+fn eval_statements(
     stmts: &[Statement],
     env: Rc<RefCell<Environment>>,
 ) -> Result<Rc<Object>, EvalError> {
@@ -69,38 +199,59 @@ fn eval_statement(
     env: Rc<RefCell<Environment>>,
 ) -> Result<Rc<Object>, EvalError> {
     match stmt {
-        Statement::Let(stmt) => {
-            let val = eval_expression(&stmt.value, env.clone())?;
-            env.borrow_mut().set(stmt.identifier.name.to_string(), val);
-            Ok(Rc::new(Object::Null))
-        }
+        Statement::Let(stmt) => eval_let_statement(stmt, env, false),
+        Statement::Const(stmt) => eval_let_statement(stmt, env, true),
         Statement::Return(stmt) => {
             let value = eval_expression(&stmt.value, env)?;
             Ok(Rc::new(Object::ReturnValue(value)))
         }
         Statement::Expression(expr) => eval_expression(&expr.expr, env),
+        Statement::Block(block) => {
+            let block_env = enclosed_env(env);
+            eval_block_statement(block, block_env)
+        }
+    }
+}
+
+fn eval_let_statement(
+    stmt: &LetStatement,
+    env: Rc<RefCell<Environment>>,
+    constant: bool,
+) -> Result<Rc<Object>, EvalError> {
+    let val = eval_expression(&stmt.value, env.clone())?;
+    let name = stmt.identifier.name.to_string();
+    if constant {
+        env.borrow_mut().set_const(name, val)?;
+    } else {
+        env.borrow_mut().set(name, val)?;
     }
+    Ok(Rc::new(Object::Null))
 }
 
+// Arms with locals of their own call out to separate functions. Unoptimized builds reserve
+// space for every arm's locals in this frame, and it is on the stack several times per call.
 fn eval_expression(
     expr: &Expression,
     env: Rc<RefCell<Environment>>,
 ) -> Result<Rc<Object>, EvalError> {
+    check_deadline()?;
+
     match expr {
         Expression::Identifier(expr) => eval_identifier(expr, env),
         Expression::Integer(expr) => Ok(Rc::new(Object::Integer(expr.value))),
+        Expression::Float(expr) => Ok(Rc::new(Object::Float(expr.value))),
         Expression::Boolean(expr) => Ok(get_bool_object(expr.value)),
         Expression::String(expr) => Ok(Rc::new(Object::String(expr.value.to_owned()))),
-        Expression::Prefix(expr) => {
-            let rhs = eval_expression(&expr.operand, env)?;
-            eval_prefix_expression(expr.operator.get_literal(), &rhs)
-        }
-        Expression::Infix(expr) => {
-            let lhs = eval_expression(&expr.lhs, env.clone())?;
-            let rhs = eval_expression(&expr.rhs, env.clone())?;
-            eval_infix_expression(expr.operator.get_literal(), &lhs, &rhs)
-        }
+        Expression::Prefix(expr) => eval_prefix_operand(expr, env),
+        Expression::Infix(expr) => eval_infix_operands(expr, env),
+        Expression::Comparison(expr) => eval_comparison_expression(expr, env),
         Expression::If(expr) => eval_if_expression(expr, env),
+        Expression::Do(expr) => {
+            let block_env = enclosed_env(env);
+            eval_block_statement(&expr.body, block_env)
+        }
+        Expression::Try(expr) => eval_try_expression(expr, env),
+        Expression::Match(expr) => eval_match_expression(expr, env),
         Expression::FnLiteral(expr) => Ok(Rc::new(Object::Function {
             parameters: expr.parameters.to_owned(),
             body: expr.body.to_owned(),
@@ -111,21 +262,12 @@ fn eval_expression(
             env,
         )?))),
         Expression::HashLiteral(expr) => eval_hash_literal(expr, env),
-        Expression::Call(expr) => {
-            let function = eval_expression(&expr.function, env.clone())?;
-            let args = eval_expressions(&expr.arguments, env.clone())?;
-            if args.len() == 1 {
-                if let Object::Error(_) = args[0].as_ref() {
-                    return Ok(args[0].clone());
-                }
-            }
-            apply_function(&function, &args)
-        }
-        Expression::Index(expr) => {
-            let identifier = eval_expression(&expr.identifier, env.clone())?;
-            let index = eval_expression(&expr.index, env)?;
-            eval_index_expression(&identifier, &index)
+        Expression::Call(expr) if expr.arguments.contains(&Expression::Placeholder) => {
+            eval_partial_application(expr, env)
         }
+        Expression::Call(expr) => eval_call_expression(expr, env),
+        Expression::Index(expr) => eval_index_operands(expr, env),
+        Expression::Placeholder => Err("placeholder outside of call arguments".to_string().into()),
     }
 }
 
@@ -137,20 +279,27 @@ fn eval_expressions(
 
     for expr in exprs.iter() {
         let evaluated = eval_expression(expr, env.clone())?;
+        if let Object::Error(message) = evaluated.as_ref() {
+            return Err(message.to_owned().into());
+        }
         result.push(evaluated);
     }
     Ok(result)
 }
 
+fn eval_prefix_operand(
+    expr: &PrefixExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    let rhs = eval_expression(&expr.operand, env)?;
+    eval_prefix_expression(expr.operator.get_literal(), &rhs)
+}
+
 fn eval_prefix_expression(prefix: String, expr: &Object) -> Result<Rc<Object>, EvalError> {
     match prefix {
         prefix if prefix == *"!" => eval_bang_operator_expression(expr),
         prefix if prefix == *"-" => eval_minus_operator_expression(expr),
-        _ => Err(format!(
-            "unknown operator: {}{}",
-            prefix,
-            expr.get_type_str()
-        )),
+        _ => Err(format!("unknown operator: {}{}", prefix, expr.get_type_str()).into()),
     }
 }
 
@@ -165,11 +314,41 @@ fn eval_bang_operator_expression(expr: &Object) -> Result<Rc<Object>, EvalError>
 
 fn eval_minus_operator_expression(expr: &Object) -> Result<Rc<Object>, EvalError> {
     match expr {
-        Object::Integer(value) => Ok(Rc::new(Object::Integer(-value))),
-        _ => Err(format!("unknown operator: -{}", expr.get_type_str())),
+        Object::Integer(value) => match value.checked_neg() {
+            Some(negated) => Ok(Rc::new(Object::Integer(negated))),
+            None => Err(format!("integer overflow: -({})", value).into()),
+        },
+        Object::Float(value) => Ok(Rc::new(Object::Float(-value))),
+        _ => Err(format!("unknown operator: -{}", expr.get_type_str()).into()),
     }
 }
 
+// Each operand is evaluated at most once, and evaluation stops at the first false comparison.
+fn eval_comparison_expression(
+    expr: &ComparisonExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    let mut lhs = eval_expression(&expr.operands[0], env.clone())?;
+    for (operator, operand) in expr.operators.iter().zip(&expr.operands[1..]) {
+        let rhs = eval_expression(operand, env.clone())?;
+        let result = eval_infix_expression(operator.get_literal(), &lhs, &rhs)?;
+        if !is_truthy(&result) {
+            return Ok(result);
+        }
+        lhs = rhs;
+    }
+    Ok(get_bool_object(true))
+}
+
+fn eval_infix_operands(
+    expr: &InfixExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    let lhs = eval_expression(&expr.lhs, env.clone())?;
+    let rhs = eval_expression(&expr.rhs, env)?;
+    eval_infix_expression(expr.operator.get_literal(), &lhs, &rhs)
+}
+
 fn eval_infix_expression(
     operator: String,
     lhs: &Object,
@@ -179,36 +358,82 @@ fn eval_infix_expression(
         (Object::Integer(lhs_value), Object::Integer(rhs_value)) => {
             eval_integer_infix_expression(&operator, lhs_value, rhs_value)
         }
+        (Object::Float(lhs_value), Object::Float(rhs_value)) => {
+            eval_float_infix_expression(&operator, *lhs_value, *rhs_value)
+        }
+        (Object::Integer(lhs_value), Object::Float(rhs_value)) => {
+            eval_float_infix_expression(&operator, *lhs_value as f64, *rhs_value)
+        }
+        (Object::Float(lhs_value), Object::Integer(rhs_value)) => {
+            eval_float_infix_expression(&operator, *lhs_value, *rhs_value as f64)
+        }
         (Object::Boolean(lhs_value), Object::Boolean(rhs_value)) => {
             eval_boolean_infix_expression(&operator, lhs_value, rhs_value)
         }
         (Object::String(lhs_value), Object::String(rhs_value)) => {
             eval_string_infix_expression(&operator, lhs_value, rhs_value)
         }
+        (Object::Array(lhs_elements), Object::Array(rhs_elements)) => {
+            eval_array_infix_expression(&operator, lhs_elements, rhs_elements)
+        }
         _ => Err(format!(
             "unknown operator: {} {} {}",
             lhs.get_type_str(),
             operator,
             rhs.get_type_str(),
-        )),
+        )
+        .into()),
+    }
+}
+
+fn eval_array_infix_expression(
+    operator: &str,
+    lhs: &[Rc<Object>],
+    rhs: &[Rc<Object>],
+) -> Result<Rc<Object>, EvalError> {
+    match operator {
+        "==" => Ok(get_bool_object(arrays_equal(lhs, rhs))),
+        "!=" => Ok(get_bool_object(!arrays_equal(lhs, rhs))),
+        _ => Err(format!("unknown operator: ARRAY {} ARRAY", operator).into()),
     }
 }
 
 fn eval_integer_infix_expression(
     operator: &str,
-    lhs: &i32,
-    rhs: &i32,
+    lhs: &Integer,
+    rhs: &Integer,
 ) -> Result<Rc<Object>, EvalError> {
     match operator {
         "+" => Ok(Rc::new(Object::Integer(lhs + rhs))),
         "-" => Ok(Rc::new(Object::Integer(lhs - rhs))),
         "*" => Ok(Rc::new(Object::Integer(lhs * rhs))),
+        "/" if *rhs == 0 => Err("division by zero".to_string().into()),
         "/" => Ok(Rc::new(Object::Integer(lhs / rhs))),
+        "**" => Ok(Rc::new(Object::Integer(integer_pow(*lhs, *rhs)?))),
+        "<" => Ok(get_bool_object(lhs < rhs)),
+        ">" => Ok(get_bool_object(lhs > rhs)),
+        "==" => Ok(get_bool_object(lhs == rhs)),
+        "!=" => Ok(get_bool_object(lhs != rhs)),
+        _ => Err(format!("unknown operator: INTEGER {} INTEGER", operator,).into()),
+    }
+}
+
+fn eval_float_infix_expression(
+    operator: &str,
+    lhs: f64,
+    rhs: f64,
+) -> Result<Rc<Object>, EvalError> {
+    match operator {
+        "+" => Ok(Rc::new(Object::Float(lhs + rhs))),
+        "-" => Ok(Rc::new(Object::Float(lhs - rhs))),
+        "*" => Ok(Rc::new(Object::Float(lhs * rhs))),
+        "/" => Ok(Rc::new(Object::Float(lhs / rhs))),
+        "**" => Ok(Rc::new(Object::Float(lhs.powf(rhs)))),
         "<" => Ok(get_bool_object(lhs < rhs)),
         ">" => Ok(get_bool_object(lhs > rhs)),
         "==" => Ok(get_bool_object(lhs == rhs)),
         "!=" => Ok(get_bool_object(lhs != rhs)),
-        _ => Err(format!("unknown operator: INTEGER {} INTEGER", operator,)),
+        _ => Err(format!("unknown operator: FLOAT {} FLOAT", operator,).into()),
     }
 }
 
@@ -220,7 +445,7 @@ fn eval_boolean_infix_expression(
     match operator {
         "==" => Ok(get_bool_object(lhs == rhs)),
         "!=" => Ok(get_bool_object(lhs != rhs)),
-        _ => Err(format!("unknown operator: BOOLEAN {} BOOLEAN", operator,)),
+        _ => Err(format!("unknown operator: BOOLEAN {} BOOLEAN", operator,).into()),
     }
 }
 
@@ -231,7 +456,7 @@ fn eval_string_infix_expression(
 ) -> Result<Rc<Object>, EvalError> {
     match operator {
         "+" => Ok(Rc::new(Object::String([lhs, rhs].join("")))),
-        _ => Err(format!("unknown operator: STRING {} STRING", operator,)),
+        _ => Err(format!("unknown operator: STRING {} STRING", operator,).into()),
     }
 }
 
@@ -240,16 +465,148 @@ fn eval_if_expression(
     env: Rc<RefCell<Environment>>,
 ) -> Result<Rc<Object>, EvalError> {
     let condition = eval_expression(&expr.condition, env.clone())?;
+    let block_env = enclosed_env(env);
 
     if is_truthy(&condition) {
-        eval_block_statement(&expr.consequence, env)
+        eval_block_statement(&expr.consequence, block_env)
     } else if let Some(alternative) = &expr.alternative {
-        eval_block_statement(alternative, env)
+        eval_block_statement(alternative, block_env)
     } else {
         Ok(Rc::new(Object::Null))
     }
 }
 
+fn eval_try_expression(
+    expr: &TryExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    let block_env = enclosed_env(env.clone());
+    let message = match eval_block_statement(&expr.body, block_env) {
+        Ok(result) => match result.as_ref() {
+            Object::Error(message) => message.to_owned(),
+            _ => return Ok(result),
+        },
+        Err(EvalError::Error(message)) => message,
+        Err(err) => return Err(err),
+    };
+    // A script must not be able to catch its way past the host's time limit.
+    if deadline_passed() {
+        return Err(TIMEOUT_ERROR.to_string().into());
+    }
+    clear_error_context();
+
+    let handler_env = enclosed_env(env);
+    handler_env
+        .borrow_mut()
+        .set(expr.error.name.to_owned(), Rc::new(Object::String(message)))?;
+    eval_block_statement(&expr.handler, handler_env)
+}
+
+fn eval_match_expression(
+    expr: &MatchExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    let subject = eval_expression(&expr.subject, env.clone())?;
+
+    // A bare identifier binds the subject whether or not the arm has a guard, so comparing
+    // against a variable's value takes a guard: `case n if n == limit`.
+    for arm in expr.arms.iter() {
+        let arm_env = enclosed_env(env.clone());
+        match &arm.value {
+            Expression::Identifier(identifier) => arm_env
+                .borrow_mut()
+                .set(identifier.name.to_owned(), subject.clone())?,
+            value => {
+                if eval_expression(value, env.clone())? != subject {
+                    continue;
+                }
+            }
+        }
+        if let Some(guard) = &arm.guard {
+            let passed = eval_expression(guard, arm_env.clone())?;
+            if !is_truthy(&passed) {
+                continue;
+            }
+        }
+        return eval_expression(&arm.body, arm_env);
+    }
+
+    match &expr.default {
+        Some(default) => eval_expression(default, env),
+        None => Ok(Rc::new(Object::Null)),
+    }
+}
+
+// Builds `fn(x0, ...) { f(a0, x0, ...) }`, binding the callee and the fixed arguments in an
+// environment of their own so each placeholder becomes a parameter of the new function.
+fn eval_call_expression(
+    expr: &CallExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    let function = eval_expression(&expr.function, env.clone())?;
+    let args = eval_expressions(&expr.arguments, env.clone())?;
+    match function.as_ref() {
+        Object::Function { .. } => apply_function(&function, &args).inspect_err(|_| {
+            push_error_context(match expr.function.as_ref() {
+                Expression::Identifier(identifier) => identifier.name.as_str(),
+                _ => "<anonymous>",
+            })
+        }),
+        // These run in the caller's environment, which builtins can't otherwise see.
+        Object::BuiltinFn(builtin) if builtin.arity == Some(args.len()) => match builtin.name {
+            "eval" => eval_quoted(&args[0], env),
+            "globals" => Ok(bindings_hash(env.borrow().global_bindings())),
+            "locals" => Ok(bindings_hash(env.borrow().local_bindings())),
+            _ => apply_function(&function, &args),
+        },
+        _ => apply_function(&function, &args),
+    }
+}
+
+fn eval_partial_application(
+    expr: &CallExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    let partial_env = enclosed_env(env.clone());
+    partial_env.borrow_mut().set(
+        "f".to_string(),
+        eval_expression(&expr.function, env.clone())?,
+    )?;
+
+    let mut parameters = Vec::new();
+    let mut arguments = Vec::new();
+    for (i, arg) in expr.arguments.iter().enumerate() {
+        let name = match arg {
+            Expression::Placeholder => {
+                let name = format!("x{}", parameters.len());
+                parameters.push(IdentifierExpression { name: name.clone() });
+                name
+            }
+            arg => {
+                let name = format!("a{}", i);
+                let val = eval_expression(arg, env.clone())?;
+                partial_env.borrow_mut().set(name.clone(), val)?;
+                name
+            }
+        };
+        arguments.push(Expression::Identifier(IdentifierExpression { name }));
+    }
+
+    let call = Expression::Call(CallExpression {
+        function: Box::new(Expression::Identifier(IdentifierExpression {
+            name: "f".to_string(),
+        })),
+        arguments,
+    });
+    Ok(Rc::new(Object::Function {
+        parameters,
+        body: BlockStatement {
+            statements: vec![Statement::Expression(ExpressionStatement { expr: call })],
+        },
+        env: partial_env,
+    }))
+}
+
 fn eval_identifier(
     identifier: &IdentifierExpression,
     env: Rc<RefCell<Environment>>,
@@ -258,32 +615,110 @@ fn eval_identifier(
     match env.borrow().get(value) {
         Some(val) => Ok(val),
         None => match get_builtin_fn(value) {
+            Some(_)
+                if env.borrow().is_sandboxed()
+                    && SANDBOX_DISABLED_BUILTINS.contains(&value.as_str()) =>
+            {
+                Err(format!("builtin not available in sandbox: {}", value).into())
+            }
             Some(builtin) => Ok(Rc::new(Object::BuiltinFn(builtin))),
-            None => Err(format!("identifier not found: {}", value)),
+            None => Err(format!("identifier not found: {}", value).into()),
+        },
+    }
+}
+
+// Evaluates a quoted node, or Monkey source, for the `eval` builtin.
+fn eval_quoted(obj: &Object, env: Rc<RefCell<Environment>>) -> Result<Rc<Object>, EvalError> {
+    match obj {
+        Object::Quote(node) => match node.as_ref() {
+            Node::Program(program) => eval_statements(&program.0, env),
+            Node::Statement(stmt) => eval_statements(slice::from_ref(stmt), env),
+            Node::Expression(expr) => eval_expression(expr, env),
         },
+        Object::String(source) => match parse_program(source) {
+            Ok(program) => eval_statements(&program.0, env),
+            Err(errs) => Err(format!("parse error: {}", errs.join("; ")).into()),
+        },
+        _ => Err(format!(
+            "argument to 'eval' must be QUOTE or STRING, found {}",
+            obj.get_type_str()
+        )
+        .into()),
+    }
+}
+
+// Functions are shown by a placeholder rather than their whole body.
+fn bindings_hash(bindings: BTreeMap<String, Rc<Object>>) -> Rc<Object> {
+    let mut pairs = HashPairs::new();
+    for (name, val) in bindings {
+        let val = match val.as_ref() {
+            Object::Function { .. } => Rc::new(Object::String("<function>".to_string())),
+            Object::BuiltinFn(builtin) => {
+                Rc::new(Object::String(format!("<builtin {}>", builtin.name)))
+            }
+            _ => val,
+        };
+        pairs.insert(
+            HashKey::String(name.clone()),
+            HashPair {
+                key: Rc::new(Object::String(name)),
+                value: val,
+            },
+        );
     }
+    Rc::new(Object::Hash(pairs))
 }
 
-fn apply_function(function: &Object, args: &[Rc<Object>]) -> Result<Rc<Object>, EvalError> {
+pub(crate) fn apply_function(
+    function: &Object,
+    args: &[Rc<Object>],
+) -> Result<Rc<Object>, EvalError> {
     match function {
         Object::Function {
             parameters,
             body,
             env,
         } => {
-            let extended_env = Rc::new(RefCell::new(extend_function_env(
-                parameters,
-                env.clone(),
-                args,
-            )));
+            let extended_env = extend_function_env(parameters, env.clone(), args)?;
             let evaluated = eval_block_statement(body, extended_env)?;
             if let Object::ReturnValue(value) = evaluated.as_ref() {
                 return Ok(value.clone());
             }
             Ok(evaluated)
         }
-        Object::BuiltinFn(builtin) => Ok(builtin(args)),
-        _ => Err(format!("not a function: {}", function.get_type_str(),)),
+        // Any error a builtin returns aborts evaluation, not just `raise`, so that a failed call
+        // in the middle of a block can't be silently dropped and `try` can catch it.
+        Object::BuiltinFn(builtin) => {
+            let result = builtin.call(args);
+            match result.as_ref() {
+                Object::Error(message) => Err(message.to_owned().into()),
+                Object::Exit(code) => Err(EvalError::Exit(*code)),
+                _ => Ok(result),
+            }
+        }
+        // Builtins called from the VM are passed compiled closures.
+        Object::Closure(closure) => {
+            let result = run_closure(closure, args)?;
+            match result.as_ref() {
+                Object::Exit(code) => Err(EvalError::Exit(*code)),
+                _ => Ok(result),
+            }
+        }
+        _ => Err(format!("not a function: {}", function.get_type_str(),).into()),
+    }
+}
+
+// Builtins don't know the name a function was passed under, so errors from the functions they
+// call are recorded like those from any other unnamed call.
+pub(crate) fn apply_callback(
+    function: &Object,
+    args: &[Rc<Object>],
+) -> Result<Rc<Object>, EvalError> {
+    match function {
+        Object::Function { .. } => {
+            apply_function(function, args).inspect_err(|_| push_error_context("<anonymous>"))
+        }
+        _ => apply_function(function, args),
     }
 }
 
@@ -291,13 +726,53 @@ fn extend_function_env(
     parameters: &[IdentifierExpression],
     env: Rc<RefCell<Environment>>,
     args: &[Rc<Object>],
-) -> Environment {
-    let mut env = env.borrow().clone().new_enclosed();
+) -> Result<Rc<RefCell<Environment>>, EvalError> {
+    if parameters.len() != args.len() {
+        return Err(format!(
+            "wrong number of arguments: expected {}, found {}",
+            parameters.len(),
+            args.len()
+        )
+        .into());
+    }
+
+    let env = enclosed_env(env);
 
     for (i, param) in parameters.iter().enumerate() {
-        env.set(param.name.to_owned(), args[i].clone());
+        env.borrow_mut()
+            .set(param.name.to_owned(), args[i].clone())?;
     }
-    env
+    Ok(env)
+}
+
+// Builds the scope on the heap directly so that the recursive eval functions don't each
+// reserve stack space for an `Environment`.
+fn enclosed_env(env: Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+    Rc::new(RefCell::new(Environment::new_enclosed(env)))
+}
+
+fn eval_index_operands(
+    expr: &IndexExpression,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Rc<Object>, EvalError> {
+    // Indexing a bound name borrows the collection rather than taking another reference to it.
+    if let Expression::Identifier(identifier) = expr.identifier.as_ref() {
+        if env.borrow().get_ref(&identifier.name, |_| ()).is_some() {
+            let index = eval_expression(&expr.index, env.clone())?;
+            return env
+                .borrow()
+                .get_ref(&identifier.name, |collection| {
+                    eval_index_expression(collection, &index)
+                })
+                .unwrap_or_else(|| {
+                    Err(format!("identifier not found: {}", identifier.name).into())
+                });
+        }
+    }
+
+    let identifier = eval_expression(&expr.identifier, env.clone())?;
+    let index = eval_expression(&expr.index, env)?;
+    eval_index_expression(&identifier, &index)
 }
 
 fn eval_index_expression(identifier: &Object, index: &Object) -> Result<Rc<Object>, EvalError> {
@@ -305,11 +780,18 @@ fn eval_index_expression(identifier: &Object, index: &Object) -> Result<Rc<Objec
         (Object::Array(array), Object::Integer(integer)) => {
             eval_array_index_expression(array, *integer as usize)
         }
+        (Object::Range { start, end, step }, Object::Integer(integer)) => {
+            Ok(match range_get(*start, *end, *step, *integer as usize) {
+                Some(value) => Rc::new(Object::Integer(value)),
+                None => Rc::new(Object::Null),
+            })
+        }
         (Object::Hash(hash), index) => eval_hash_index_expression(hash, index),
         _ => Err(format!(
             "index operator not supported: {}",
             identifier.get_type_str()
-        )),
+        )
+        .into()),
     }
 }
 
@@ -324,10 +806,7 @@ fn eval_array_index_expression(
     Ok(array[index].clone())
 }
 
-fn eval_hash_index_expression(
-    hash: &BTreeMap<HashKey, HashPair>,
-    index: &Object,
-) -> Result<Rc<Object>, EvalError> {
+fn eval_hash_index_expression(hash: &HashPairs, index: &Object) -> Result<Rc<Object>, EvalError> {
     if let Some(hash_key) = index.get_hash_key() {
         if let Some(pair) = hash.get(&hash_key) {
             Ok(pair.value.clone())
@@ -335,7 +814,7 @@ fn eval_hash_index_expression(
             Ok(Rc::new(Object::Null))
         }
     } else {
-        Err(format!("unusable as hash key: {}", index.get_type_str()))
+        Err(format!("unusable as hash key: {}", index.get_type_str()).into())
     }
 }
 
@@ -343,7 +822,7 @@ fn eval_hash_literal(
     hash_literal: &HashLiteralExpression,
     env: Rc<RefCell<Environment>>,
 ) -> Result<Rc<Object>, EvalError> {
-    let mut pairs = BTreeMap::new();
+    let mut pairs = HashPairs::new();
 
     for (key_expr, value_expr) in hash_literal.pairs.iter() {
         let key = eval_expression(key_expr, env.clone())?;
@@ -353,7 +832,7 @@ fn eval_hash_literal(
                 pairs.insert(hash_key, HashPair { key, value });
             }
             None => {
-                return Err(format!("unusable as hash key: {}", key.get_type_str()));
+                return Err(format!("unusable as hash key: {}", key.get_type_str()).into());
             }
         }
     }
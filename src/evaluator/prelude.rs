@@ -0,0 +1,46 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::{environment::Environment, eval_program};
+use crate::{object::Object, parser::parse_program};
+
+// Nothing here recurses once per element: `map` loops in `repeat`, and `filter` and `reduce` split
+// the array in half, so arrays of any length stay within the evaluator's stack.
+pub const PRELUDE: &str = "
+let map = fn(arr, f) {
+    repeat(len(arr), fn(i) { f(arr[i]) })
+};
+
+let filter = fn(arr, predicate) {
+    let n = len(arr);
+    if (n > 1) {
+        let half = n / 2;
+        concat(filter(take(arr, half), predicate), filter(drop(arr, half), predicate))
+    } else {
+        if (n == 1) { if (predicate(arr[0])) { arr } else { [] } } else { [] }
+    }
+};
+
+let reduce = fn(arr, initial, f) {
+    let n = len(arr);
+    if (n > 1) {
+        let half = n / 2;
+        reduce(drop(arr, half), reduce(take(arr, half), initial, f), f)
+    } else {
+        if (n == 1) { f(initial, arr[0]) } else { initial }
+    }
+};
+";
+
+// The prelude functions close over the environment they were defined in, so the returned copy
+// can be extended freely without affecting them.
+pub fn with_prelude() -> Environment {
+    let env = Rc::new(RefCell::new(Environment::new()));
+    let program = parse_program(PRELUDE).expect("error occurred while parsing prelude");
+
+    if let Object::Error(err) = eval_program(&program, env.clone()).as_ref() {
+        panic!("error occurred while evaluating prelude: {}", err);
+    }
+
+    let prelude = env.borrow().clone();
+    prelude
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        evaluator::{environment::Environment, eval_program},
+        object::Object,
+        parser::parse_program,
+    };
+
+    fn eval_in(input: &str, env: &Rc<RefCell<Environment>>) -> Rc<Object> {
+        let program = parse_program(input).expect("error occurred while parsing program");
+        eval_program(&program, env.clone())
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let env = Rc::new(RefCell::new(Environment::new()));
+        eval_in("let x = 1; const c = 2;", &env);
+
+        let snapshot = env.borrow().snapshot();
+        eval_in("let x = 10; let y = 20;", &env);
+        assert_eq!(env.borrow().get("x"), Some(Rc::new(Object::Integer(10))));
+        assert_eq!(env.borrow().get("y"), Some(Rc::new(Object::Integer(20))));
+
+        env.borrow_mut().restore(snapshot.clone());
+        assert_eq!(env.borrow().get("x"), Some(Rc::new(Object::Integer(1))));
+        assert_eq!(env.borrow().get("y"), None);
+        assert_eq!(env.borrow().snapshot(), snapshot);
+        assert_eq!(
+            *eval_in("let c = 3;", &env),
+            Object::Error("cannot reassign constant: c".to_string())
+        );
+    }
+
+    #[test]
+    fn test_restore_leaves_outer_environment() {
+        let outer = Rc::new(RefCell::new(Environment::new()));
+        let inner = Rc::new(RefCell::new(Environment::new_enclosed(outer.clone())));
+
+        let snapshot = inner.borrow().snapshot();
+        eval_in("let x = 1;", &outer);
+        eval_in("let y = 2;", &inner);
+
+        inner.borrow_mut().restore(snapshot);
+        assert_eq!(inner.borrow().get("x"), Some(Rc::new(Object::Integer(1))));
+        assert_eq!(inner.borrow().get("y"), None);
+    }
+
+    #[test]
+    fn test_get_ref() {
+        let outer = Rc::new(RefCell::new(Environment::new()));
+        eval_in("let arr = [1, 2, 3];", &outer);
+        let env = Rc::new(RefCell::new(Environment::new_enclosed(outer.clone())));
+
+        assert_eq!(
+            env.borrow().get_ref("arr", |arr| arr.to_string()),
+            Some("[1, 2, 3]".to_string())
+        );
+        assert_eq!(env.borrow().get_ref("missing", |_| ()), None);
+
+        // The outer scope is shared rather than copied, so later bindings are visible.
+        eval_in("let later = 4;", &outer);
+        assert_eq!(
+            env.borrow().get_ref("later", |later| later.clone()),
+            Some(Object::Integer(4))
+        );
+    }
+
+    #[test]
+    fn test_complete() {
+        let outer = Rc::new(RefCell::new(Environment::new()));
+        eval_in("let length = 1; let lerp = fn(a, b) { a };", &outer);
+        let env = Rc::new(RefCell::new(Environment::new_enclosed(outer)));
+        eval_in("let left = 2;", &env);
+
+        assert_eq!(
+            env.borrow().complete("le"),
+            vec!["left", "len", "length", "lerp"]
+        );
+        assert_eq!(env.borrow().complete("len"), vec!["len", "length"]);
+        assert_eq!(env.borrow().complete("pu"), vec!["push", "put", "puts"]);
+        assert!(env.borrow().complete("nonexistent").is_empty());
+        assert!(env.borrow().complete("").contains(&"lerp".to_string()));
+    }
+}
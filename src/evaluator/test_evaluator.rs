@@ -1,19 +1,24 @@
 #[cfg(test)]
 mod tests {
-    use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+    use std::{cell::RefCell, rc::Rc, time::Duration};
 
     use crate::{
         evaluator::environment::Environment,
-        evaluator::eval,
-        object::{HashPair, Object},
-        parser::parse,
+        evaluator::prelude::with_prelude,
+        evaluator::{error_context, eval, eval_each, eval_program, eval_with_timeout, run},
+        lexer::token::Token,
+        object::{HashPair, HashPairs, Integer, Object},
+        parser::{
+            ast::{Expression, InfixExpression, IntegerExpression, Node},
+            parse, parse_program,
+        },
     };
 
     fn test_evaluating(input: &str, expected: Object) {
         let env = Rc::new(RefCell::new(Environment::new()));
 
         let program = parse(input).expect("error occurred while parsing program");
-        let eval_input = eval(program, env);
+        let eval_input = eval(&program, env);
 
         assert_eq!(eval_input, Rc::new(expected));
     }
@@ -37,11 +42,11 @@ mod tests {
     #[test]
     fn test_eval_return_expressions() {
         let inputs = [
-            "return 10;",
-            "return 10; 9;",
-            "return 2 * 5; 9;",
-            "9; return 2 * 5; 9;",
-            "if (10 > 1) { if (10 > 1) { return 10; }} return 1;",
+            "fn() { return 10; }()",
+            "fn() { return 10; 9; }()",
+            "fn() { return 2 * 5; 9; }()",
+            "fn() { 9; return 2 * 5; 9; }()",
+            "fn() { if (10 > 1) { if (10 > 1) { return 10; }} return 1; }()",
         ];
         let expected_values = [10, 10, 10, 10, 10];
 
@@ -64,8 +69,10 @@ mod tests {
             "5 + 2 * 10",
             "50 / 2 * 2 + 10",
             "(5 + 10 * 2 + 15 / 3) * 2 + -10",
+            "-2 ** 2",
+            "2 ** 3 ** 2",
         ];
-        let expected_values = [5, 10, -5, -10, 10, 32, 20, 25, 60, 50];
+        let expected_values = [5, 10, -5, -10, 10, 32, 20, 25, 60, 50, -4, 512];
 
         for (i, input) in inputs.iter().enumerate() {
             let expected = Object::Integer(expected_values[i]);
@@ -73,6 +80,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_eval_float_expressions() {
+        let inputs = [
+            "2.5",
+            "-2.5",
+            "1e3",
+            "2.5e-1",
+            "6.02E23",
+            "1.5 + 1",
+            "3 * 0.5",
+            "1 / 4.0",
+            "2.0 ** 0.5 ** 2",
+        ];
+        let expected_values = [
+            2.5,
+            -2.5,
+            1000.0,
+            0.25,
+            6.02e23,
+            2.5,
+            1.5,
+            0.25,
+            2.0_f64.powf(0.25),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = Object::Float(expected_values[i]);
+            test_evaluating(input, expected);
+        }
+
+        let inputs = ["1e3 == 1000.0", "2.5e-1 == 0.25", "1.5 > 1", "0.1 < 0.01"];
+        let expected_values = [true, true, true, false];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = Object::Boolean(expected_values[i]);
+            test_evaluating(input, expected);
+        }
+    }
+
     #[test]
     fn test_eval_bool_expressions() {
         let inputs = [
@@ -114,6 +160,156 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(not(feature = "i64"))]
+    fn test_eval_minus_overflow() {
+        let inputs = [
+            "-(-2147483647)",
+            "let min = -2147483647 - 1; min",
+            "let min = -2147483647 - 1; -min",
+        ];
+        let expected_values = [
+            Object::Integer(2147483647),
+            Object::Integer(i32::MIN),
+            Object::Error("integer overflow: -(-2147483648)".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_eval_wide_integers() {
+        let inputs = [
+            "2147483647 + 1",
+            "3000000000 * 2",
+            "2 ** 40",
+            "-(-2147483647 - 1)",
+            "let fact = fn(n) { if (n < 2) { 1 } else { n * fact(n - 1) } }; fact(20)",
+            "len(range(2147483640, 2147483660))",
+        ];
+        let expected_values = [
+            Object::Integer(2147483648),
+            Object::Integer(6000000000),
+            Object::Integer(1099511627776),
+            Object::Integer(2147483648),
+            Object::Integer(2432902008176640000),
+            Object::Integer(20),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_empty_programs() {
+        let inputs = [
+            "",
+            "   \n\t  ",
+            "// nothing to see here",
+            "// one\n\n// two\n",
+        ];
+
+        for input in inputs.iter() {
+            test_evaluating(input, Object::Null);
+            assert_eq!(*run(input), Object::Null);
+        }
+    }
+
+    #[test]
+    fn test_eval_block_statements() {
+        let inputs = [
+            "{ let x = 1; x + 1 }",
+            "let x = 1; { let x = 2; x }; x",
+            "{ let y = 2; }; y",
+            "let x = 1; { let y = x + 1; { y * 10 } }",
+            "let f = fn() { { return 5; }; 10 }; f()",
+            "{ }",
+            "{ let x = 1; }",
+        ];
+        let expected_values = [
+            Object::Integer(2),
+            Object::Integer(1),
+            Object::Error("identifier not found: y".to_string()),
+            Object::Integer(20),
+            Object::Integer(5),
+            Object::Hash(HashPairs::new()),
+            Object::Null,
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_array_equality() {
+        let inputs = [
+            "[1, 2, 3] == [1, 2, 3]",
+            "[1, 2, 3] != [1, 2, 3]",
+            "[1, 2, 3] == [1, 2]",
+            "[1, [2, 3]] == [1, [2, 3]]",
+            "[1, 2] == [1, \"2\"]",
+            "[] == []",
+            "to_array(range(0, 100000)) == to_array(range(0, 100001))",
+            "to_array(range(0, 100000)) == to_array(range(0, 100000))",
+            "[1] < [2]",
+        ];
+        let expected_values = [
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Error("unknown operator: ARRAY < ARRAY".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_chained_comparisons() {
+        let inputs = [
+            "1 < 5 < 10",
+            "1 < 20 < 10",
+            "10 > 5 > 1",
+            "1 < 5 > 3",
+            "let x = 7; 0 < x < 10 == true",
+            "1.5 < 2 < 2.5",
+            "2 < 1 < missing",
+            "1 < 2 < missing",
+            "(1 < 2) < 3",
+        ];
+        let expected_values = [
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Error("identifier not found: missing".to_string()),
+            Object::Error("unknown operator: BOOLEAN < INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
     #[test]
     fn test_eval_prefix_expressions() {
         let inputs = ["!true", "!false"];
@@ -222,118 +418,72 @@ mod tests {
     }
 
     #[test]
-    fn test_eval_array_literal() {
-        let input = "[1, 2 * 2, 3 + 3]";
+    fn test_eval_deep_equal() {
+        let inputs = [
+            "deep_equal([[1], [2]], [[1], [2]])",
+            "deep_equal([[1], [2]], [[1], [3]])",
+            "deep_equal([1, 2], [1, 2, 3])",
+            "deep_equal({\"a\": [1, {true: \"x\"}], 2: 3}, {2: 3, \"a\": [1, {true: \"x\"}]})",
+            "deep_equal({\"a\": [1, {true: \"x\"}]}, {\"a\": [1, {true: \"y\"}]})",
+            "deep_equal({\"a\": 1}, {\"b\": 1})",
+            "deep_equal(1, \"1\")",
+            "let f = fn(x) { if (x > 0) { f(x - 1) } else { x } }; deep_equal([f], [f])",
+            "deep_equal(1)",
+        ];
+        let expected_values = [
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(false),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Error("wrong number of arguments: expected 2, found 1".to_string()),
+        ];
 
-        let expected = Object::Array(vec![
-            Rc::new(Object::Integer(1)),
-            Rc::new(Object::Integer(4)),
-            Rc::new(Object::Integer(6)),
-        ]);
-        test_evaluating(input, expected);
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
     }
 
     #[test]
-    fn test_eval_hash_literal() {
-        let input = "let two = \"two\";
-            { 
-                \"one\": 10 - 9,
-                two: 1 + 1,
-                \"thr\" + \"ee\": 6 / 2,
-                4: 4,
-                true: 5,
-                false: 6
-            }";
-
-        let mut expected_pairs = BTreeMap::new();
-        let key_one = Object::String("one".to_string());
-        expected_pairs.insert(
-            key_one
-                .get_hash_key()
-                .expect("error occurred while getting hash key"),
-            HashPair {
-                key: Rc::new(key_one),
-                value: Rc::new(Object::Integer(1)),
-            },
-        );
-        let key_two = Object::String("two".to_string());
-        expected_pairs.insert(
-            key_two
-                .get_hash_key()
-                .expect("error occurred while getting hash key"),
-            HashPair {
-                key: Rc::new(key_two),
-                value: Rc::new(Object::Integer(2)),
-            },
-        );
-        let key_three = Object::String("three".to_string());
-        expected_pairs.insert(
-            key_three
-                .get_hash_key()
-                .expect("error occurred while getting hash key"),
-            HashPair {
-                key: Rc::new(key_three),
-                value: Rc::new(Object::Integer(3)),
-            },
-        );
-        let key_four = Object::Integer(4);
-        expected_pairs.insert(
-            key_four
-                .get_hash_key()
-                .expect("error occurred while getting hash key"),
-            HashPair {
-                key: Rc::new(key_four.clone()),
-                value: Rc::new(key_four),
-            },
-        );
-        let key_five = Object::Boolean(true);
-        expected_pairs.insert(
-            key_five
-                .get_hash_key()
-                .expect("error occurred while getting hash key"),
-            HashPair {
-                key: Rc::new(key_five),
-                value: Rc::new(Object::Integer(5)),
-            },
-        );
-        let key_six = Object::Boolean(false);
-        expected_pairs.insert(
-            key_six
-                .get_hash_key()
-                .expect("error occurred while getting hash key"),
-            HashPair {
-                key: Rc::new(key_six),
-                value: Rc::new(Object::Integer(6)),
-            },
-        );
+    fn test_eval_apply() {
+        let inputs = [
+            "let f = fn(a, b, c) { a * 100 + b * 10 + c }; apply(f, [1, 2, 3])",
+            "apply(len, [[1, 2]])",
+            "let f = fn(a, b) { a }; apply(f, [1])",
+            "apply(1, [1])",
+            "apply(len, 1)",
+        ];
+        let expected_values = [
+            Object::Integer(123),
+            Object::Integer(2),
+            Object::Error("wrong number of arguments: expected 2, found 1".to_string()),
+            Object::Error("first argument to 'apply' must be FUNCTION, found INTEGER".to_string()),
+            Object::Error("second argument to 'apply' must be ARRAY, found INTEGER".to_string()),
+        ];
 
-        let expected = Object::Hash(expected_pairs);
-        test_evaluating(input, expected);
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
     }
 
     #[test]
-    fn test_eval_array_index_expressions() {
+    fn test_eval_compose() {
         let inputs = [
-            "[1, 2, 3][0]",
-            "[1, 2, 3][1]",
-            "[1, 2, 3][2]",
-            "let i = 0; [1][i];",
-            "[1, 2, 3][1 + 1];",
-            "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
-            "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
-            "[1, 2, 3][3]",
-            "[1, 2, 3][-1]",
+            "let inc = fn(x) { x + 1 }; let double = fn(x) { x * 2 }; compose(inc, double)(3)",
+            "let inc = fn(x) { x + 1 }; let double = fn(x) { x * 2 }; compose(double, inc)(3)",
+            "let x = 10; let f = fn(y) { x + y }; compose(f, len)([1, 2])",
+            "compose(fn(x) { x }, 1)",
         ];
         let expected_values = [
-            Object::Integer(1),
-            Object::Integer(2),
-            Object::Integer(3),
-            Object::Integer(1),
-            Object::Integer(3),
-            Object::Integer(6),
-            Object::Integer(2),
-            Object::Null,
-            Object::Null,
+            Object::Integer(7),
+            Object::Integer(8),
+            Object::Integer(12),
+            Object::Error("arguments to 'compose' must be FUNCTION, found INTEGER".to_string()),
         ];
 
         for (i, input) in inputs.iter().enumerate() {
@@ -343,24 +493,38 @@ mod tests {
     }
 
     #[test]
-    fn test_eval_hash_index_expressions() {
+    fn test_eval_compose_keeps_sandbox() {
+        let env = Rc::new(RefCell::new(Environment::new_sandboxed()));
+        let program = parse("let inc = fn(x) { x + 1 }; compose(inc, len)")
+            .expect("error occurred while parsing program");
+
+        match eval(&program, env).as_ref() {
+            Object::Function { env, .. } => assert!(env.borrow().is_sandboxed()),
+            obj => panic!("expected FUNCTION, found {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_eval_repeat() {
         let inputs = [
-            "{\"foo\": 5}[\"foo\"]",
-            "{\"foo\": 5}[\"bar\"]",
-            "let key = \"foo\"; {\"foo\": 5}[key]",
-            "{}[\"foo\"]",
-            "{5: 5}[5]",
-            "{true: 5}[true]",
-            "{false: 5}[false]",
+            "repeat(3, fn(i) { i * i })",
+            "repeat(-1, fn(i) { i })",
+            "repeat(2, fn(i) { len(i) })",
+            "repeat(\"3\", fn(i) { i })",
+            "repeat(3, 3)",
         ];
         let expected_values = [
-            Object::Integer(5),
-            Object::Null,
-            Object::Integer(5),
-            Object::Null,
-            Object::Integer(5),
-            Object::Integer(5),
-            Object::Integer(5),
+            Object::Array(vec![
+                Rc::new(Object::Integer(0)),
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(4)),
+            ]),
+            Object::Array(vec![]),
+            Object::Error("argument to 'len' not supported, found INTEGER".to_string()),
+            Object::Error("first argument to 'repeat' must be INTEGER, found STRING".to_string()),
+            Object::Error(
+                "second argument to 'repeat' must be FUNCTION, found INTEGER".to_string(),
+            ),
         ];
 
         for (i, input) in inputs.iter().enumerate() {
@@ -370,31 +534,2034 @@ mod tests {
     }
 
     #[test]
-    fn test_eval_error_handling() {
+    fn test_eval_zip() {
         let inputs = [
-            "5 + true;",
-            "5 + true; 5;",
-            "-true",
-            "true * false",
-            "5; true / false; 5",
-            "if (10 > 1) { true + false; }",
-            "foobar",
-            "{\"name\": \"Monkey\"}[fn(x) { x }]",
+            "zip([1, 2, 3], [\"a\", \"b\"])",
+            "zip([1, 2, 3], [])",
+            "zip([1], 2)",
         ];
         let expected_values = [
-            "unknown operator: INTEGER + BOOLEAN",
-            "unknown operator: INTEGER + BOOLEAN",
-            "unknown operator: -BOOLEAN",
-            "unknown operator: BOOLEAN * BOOLEAN",
-            "unknown operator: BOOLEAN / BOOLEAN",
-            "unknown operator: BOOLEAN + BOOLEAN",
-            "identifier not found: foobar",
-            "unusable as hash key: FUNCTION",
+            Object::Array(vec![
+                Rc::new(Object::Array(vec![
+                    Rc::new(Object::Integer(1)),
+                    Rc::new(Object::String("a".to_string())),
+                ])),
+                Rc::new(Object::Array(vec![
+                    Rc::new(Object::Integer(2)),
+                    Rc::new(Object::String("b".to_string())),
+                ])),
+            ]),
+            Object::Array(vec![]),
+            Object::Error("second argument to 'zip' must be ARRAY, found INTEGER".to_string()),
         ];
 
         for (i, input) in inputs.iter().enumerate() {
-            let expected = Object::Error(expected_values[i].to_string());
+            let expected = expected_values[i].clone();
             test_evaluating(input, expected);
         }
     }
+
+    #[test]
+    fn test_eval_unique() {
+        let inputs = [
+            "unique([1, 1, 2, 3, 3])",
+            "unique([\"a\", true, 2])",
+            "unique([\"a\", \"b\", \"a\", false, false])",
+            "unique(1)",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(3)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::String("a".to_string())),
+                Rc::new(Object::Boolean(true)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::String("a".to_string())),
+                Rc::new(Object::String("b".to_string())),
+                Rc::new(Object::Boolean(false)),
+            ]),
+            Object::Error("argument to 'unique' must be ARRAY, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_count() {
+        let inputs = [
+            "count([1, 2, 3, 4], fn(x) { x / 2 * 2 == x })",
+            "count([], fn(x) { true })",
+            "count([1, 2], 2)",
+            "count(1, fn(x) { true })",
+        ];
+        let expected_values = [
+            Object::Integer(2),
+            Object::Integer(0),
+            Object::Error("second argument to 'count' must be FUNCTION, found INTEGER".to_string()),
+            Object::Error(
+                "first argument to 'count' must be ARRAY or RANGE, found INTEGER".to_string(),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_group_by() {
+        let inputs = [
+            "let groups = group_by([1, 2, 3, 4], fn(x) { x - x / 2 * 2 }); [groups[0], groups[1]]",
+            "group_by([\"a\", \"bb\", \"c\"], len)",
+            "group_by([], fn(x) { x })",
+            "group_by([1, 2], fn(x) { [x] })",
+            "group_by([1, 2], 3)",
+            "group_by(1, fn(x) { x })",
+        ];
+        let mut by_length = HashPairs::new();
+        for (length, strings) in [(1, vec!["a", "c"]), (2, vec!["bb"])] {
+            let key = Object::Integer(length);
+            by_length.insert(
+                key.get_hash_key()
+                    .expect("error occurred while getting hash key"),
+                HashPair {
+                    key: Rc::new(key),
+                    value: Rc::new(Object::Array(
+                        strings
+                            .into_iter()
+                            .map(|string| Rc::new(Object::String(string.to_string())))
+                            .collect(),
+                    )),
+                },
+            );
+        }
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Array(vec![
+                    Rc::new(Object::Integer(2)),
+                    Rc::new(Object::Integer(4)),
+                ])),
+                Rc::new(Object::Array(vec![
+                    Rc::new(Object::Integer(1)),
+                    Rc::new(Object::Integer(3)),
+                ])),
+            ]),
+            Object::Hash(by_length),
+            Object::Hash(HashPairs::new()),
+            Object::Error("unusable as hash key: ARRAY".to_string()),
+            Object::Error(
+                "second argument to 'group_by' must be FUNCTION, found INTEGER".to_string(),
+            ),
+            Object::Error("first argument to 'group_by' must be ARRAY, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_sort_by() {
+        let inputs = [
+            "sort_by([\"ccc\", \"a\", \"bb\"], len)",
+            "sort_by([\"bb\", \"x\", \"aa\", \"y\"], len)",
+            "let xs = [3, 1, 2]; sort_by(xs, fn(x) { -x }); xs",
+            "sort_by([2, 1], fn(x) { if (x == 1) { 1 } else { \"1\" } })",
+            "sort_by([1, 2], fn(x) { true })",
+            "sort_by([1, 2], 3)",
+        ];
+        let strings = |strings: &[&str]| {
+            Object::Array(
+                strings
+                    .iter()
+                    .map(|string| Rc::new(Object::String(string.to_string())))
+                    .collect(),
+            )
+        };
+        let expected_values = [
+            strings(&["a", "bb", "ccc"]),
+            strings(&["x", "y", "bb", "aa"]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(3)),
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Error("cannot compare keys in 'sort_by': STRING and INTEGER".to_string()),
+            Object::Error("keys in 'sort_by' must be INTEGER or STRING, found BOOLEAN".to_string()),
+            Object::Error(
+                "second argument to 'sort_by' must be FUNCTION, found INTEGER".to_string(),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_wrapping_arithmetic() {
+        let inputs = [
+            "wrapping_add(2, 3)".to_string(),
+            format!("wrapping_add({}, 1)", Integer::MAX),
+            format!("wrapping_add({}, {})", Integer::MAX, Integer::MAX),
+            format!("wrapping_sub(-{} - 1, 1)", Integer::MAX),
+            "wrapping_sub(1, 3)".to_string(),
+            format!("wrapping_mul({}, 2)", Integer::MAX),
+            "wrapping_mul(-4, 5)".to_string(),
+            format!("wrapping_add({}, 1) == -{} - 1", Integer::MAX, Integer::MAX),
+            "wrapping_add(1, true)".to_string(),
+            "wrapping_mul(\"2\", 3)".to_string(),
+        ];
+        let expected_values = [
+            Object::Integer(5),
+            Object::Integer(Integer::MIN),
+            Object::Integer(-2),
+            Object::Integer(Integer::MAX),
+            Object::Integer(-2),
+            Object::Integer(-2),
+            Object::Integer(-20),
+            Object::Boolean(true),
+            Object::Error("arguments to 'wrapping_add' must be INTEGER, found BOOLEAN".to_string()),
+            Object::Error("arguments to 'wrapping_mul' must be INTEGER, found STRING".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_take_drop() {
+        let inputs = [
+            "take([1, 2, 3, 4], 2)",
+            "drop([1, 2, 3, 4], 2)",
+            "take([1, 2], 5)",
+            "drop([1, 2], 5)",
+            "take([1, 2], -1)",
+            "drop([1, 2], -1)",
+            "take([1, 2], \"1\")",
+            "drop(1, 1)",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(3)),
+                Rc::new(Object::Integer(4)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Array(vec![]),
+            Object::Array(vec![]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Error("second argument to 'take' must be INTEGER, found STRING".to_string()),
+            Object::Error("first argument to 'drop' must be ARRAY, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_pad_string() {
+        let inputs = [
+            "pad_start(\"7\", 3, \"0\")",
+            "pad_end(\"7\", 3, \"0\")",
+            "pad_start(\"1234\", 3, \"0\")",
+            "pad_end(\"7\", -1, \" \")",
+            "pad_start(\"7\", 3, \"00\")",
+            "pad_end(\"7\", \"3\", \"0\")",
+            "pad_start(7, 3, \"0\")",
+        ];
+        let expected_values = [
+            Object::String("007".to_string()),
+            Object::String("700".to_string()),
+            Object::String("1234".to_string()),
+            Object::String("7".to_string()),
+            Object::Error(
+                "third argument to 'pad_start' must be a single character, found \"00\""
+                    .to_string(),
+            ),
+            Object::Error("second argument to 'pad_end' must be INTEGER, found STRING".to_string()),
+            Object::Error(
+                "first argument to 'pad_start' must be STRING, found INTEGER".to_string(),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_min_max_by() {
+        let inputs = [
+            "max_by([\"a\", \"abc\", \"ab\"], len)",
+            "min_by([\"ab\", \"a\", \"abc\"], len)",
+            "max_by([3, 1, 2], fn(x) { 0 - x })",
+            "max_by([1, 2], fn(x) { \"k\" })",
+            "min_by([], len)",
+            "max_by([1, \"a\"], fn(x) { x })",
+            "min_by([true], fn(x) { x })",
+            "min_by([1, 2], len)",
+            "max_by([\"a\", 2], len)",
+        ];
+        let expected_values = [
+            Object::String("abc".to_string()),
+            Object::String("a".to_string()),
+            Object::Integer(1),
+            Object::Integer(1),
+            Object::Error("argument to 'min_by' must not be an empty ARRAY".to_string()),
+            Object::Error("cannot compare keys in 'max_by': INTEGER and STRING".to_string()),
+            Object::Error("keys in 'min_by' must be INTEGER or STRING, found BOOLEAN".to_string()),
+            Object::Error("argument to 'len' not supported, found INTEGER".to_string()),
+            Object::Error("argument to 'len' not supported, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_repeated_push() {
+        let input =
+            "let build = fn(arr, n) { if (n == 0) { arr } else { build(push(arr, n), n - 1) } };
+            let arr = build([], 100);
+            let sum = fn(i) { if (i == len(arr)) { 0 } else { arr[i] + sum(i + 1) } };
+            [len(arr), first(arr), last(arr), sum(0)];";
+        let expected = Object::Array(vec![
+            Rc::new(Object::Integer(100)),
+            Rc::new(Object::Integer(100)),
+            Rc::new(Object::Integer(1)),
+            Rc::new(Object::Integer(5050)),
+        ]);
+        test_evaluating(input, expected);
+    }
+
+    #[test]
+    fn test_eval_array_literal() {
+        let input = "[1, 2 * 2, 3 + 3]";
+
+        let expected = Object::Array(vec![
+            Rc::new(Object::Integer(1)),
+            Rc::new(Object::Integer(4)),
+            Rc::new(Object::Integer(6)),
+        ]);
+        test_evaluating(input, expected);
+    }
+
+    #[test]
+    fn test_eval_hash_literal() {
+        let input = "let two = \"two\";
+            { 
+                \"one\": 10 - 9,
+                two: 1 + 1,
+                \"thr\" + \"ee\": 6 / 2,
+                4: 4,
+                true: 5,
+                false: 6
+            }";
+
+        let mut expected_pairs = HashPairs::new();
+        let key_one = Object::String("one".to_string());
+        expected_pairs.insert(
+            key_one
+                .get_hash_key()
+                .expect("error occurred while getting hash key"),
+            HashPair {
+                key: Rc::new(key_one),
+                value: Rc::new(Object::Integer(1)),
+            },
+        );
+        let key_two = Object::String("two".to_string());
+        expected_pairs.insert(
+            key_two
+                .get_hash_key()
+                .expect("error occurred while getting hash key"),
+            HashPair {
+                key: Rc::new(key_two),
+                value: Rc::new(Object::Integer(2)),
+            },
+        );
+        let key_three = Object::String("three".to_string());
+        expected_pairs.insert(
+            key_three
+                .get_hash_key()
+                .expect("error occurred while getting hash key"),
+            HashPair {
+                key: Rc::new(key_three),
+                value: Rc::new(Object::Integer(3)),
+            },
+        );
+        let key_four = Object::Integer(4);
+        expected_pairs.insert(
+            key_four
+                .get_hash_key()
+                .expect("error occurred while getting hash key"),
+            HashPair {
+                key: Rc::new(key_four.clone()),
+                value: Rc::new(key_four),
+            },
+        );
+        let key_five = Object::Boolean(true);
+        expected_pairs.insert(
+            key_five
+                .get_hash_key()
+                .expect("error occurred while getting hash key"),
+            HashPair {
+                key: Rc::new(key_five),
+                value: Rc::new(Object::Integer(5)),
+            },
+        );
+        let key_six = Object::Boolean(false);
+        expected_pairs.insert(
+            key_six
+                .get_hash_key()
+                .expect("error occurred while getting hash key"),
+            HashPair {
+                key: Rc::new(key_six),
+                value: Rc::new(Object::Integer(6)),
+            },
+        );
+
+        let expected = Object::Hash(expected_pairs);
+        test_evaluating(input, expected);
+    }
+
+    #[test]
+    fn test_eval_array_index_expressions() {
+        let inputs = [
+            "[1, 2, 3][0]",
+            "[1, 2, 3][1]",
+            "[1, 2, 3][2]",
+            "let i = 0; [1][i];",
+            "[1, 2, 3][1 + 1];",
+            "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
+            "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
+            "[1, 2, 3][3]",
+            "[1, 2, 3][-1]",
+        ];
+        let expected_values = [
+            Object::Integer(1),
+            Object::Integer(2),
+            Object::Integer(3),
+            Object::Integer(1),
+            Object::Integer(3),
+            Object::Integer(6),
+            Object::Integer(2),
+            Object::Null,
+            Object::Null,
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_hash_index_expressions() {
+        let inputs = [
+            "{\"foo\": 5}[\"foo\"]",
+            "{\"foo\": 5}[\"bar\"]",
+            "let key = \"foo\"; {\"foo\": 5}[key]",
+            "{}[\"foo\"]",
+            "{5: 5}[5]",
+            "{true: 5}[true]",
+            "{false: 5}[false]",
+        ];
+        let expected_values = [
+            Object::Integer(5),
+            Object::Null,
+            Object::Integer(5),
+            Object::Null,
+            Object::Integer(5),
+            Object::Integer(5),
+            Object::Integer(5),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_hash_function_and_array_values() {
+        let inputs = [
+            "let h = {\"double\": fn(x) { x * 2 }}; h[\"double\"](21)",
+            "let h = {\"xs\": [1, 2, 3]}; h[\"xs\"][2]",
+            "let h = {1: len}; h[1]([1, 2])",
+            "let f = fn(x) { x }; let h = {\"f\": f}; h[\"f\"] == f",
+            "{fn(x) { x }: 1}",
+            "{[1]: 1}",
+        ];
+        let expected_values = [
+            Object::Integer(42),
+            Object::Integer(3),
+            Object::Integer(2),
+            Object::Error("unknown operator: FUNCTION == FUNCTION".to_string()),
+            Object::Error("unusable as hash key: FUNCTION".to_string()),
+            Object::Error("unusable as hash key: ARRAY".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+
+        let env = Rc::new(RefCell::new(Environment::new()));
+        let program = parse("let f = fn(x, y) { x + y }; let h = {\"f\": f, \"xs\": [f]}; h")
+            .expect("error occurred while parsing program");
+        let hash = eval(&program, env.clone());
+        let f = env.borrow().get("f").expect("f not found");
+        let value = |key: &str| {
+            let hash_key = Object::String(key.to_string())
+                .get_hash_key()
+                .expect("error occurred while getting hash key");
+            hash.as_hash()
+                .and_then(|pairs| pairs.get(&hash_key))
+                .map(|pair| pair.value.clone())
+        };
+        assert!(value("f").is_some_and(|value| Rc::ptr_eq(&value, &f)));
+        assert!(value("xs")
+            .is_some_and(|value| value.as_array().is_some_and(|xs| Rc::ptr_eq(&xs[0], &f))));
+    }
+
+    #[test]
+    fn test_eval_hash_insertion_order() {
+        let inputs = [
+            "{3: \"c\", 1: \"a\", 2: \"b\"}",
+            "{\"z\": 1, \"a\": 2, true: 3, \"m\": 4}",
+            "{2: 1, 1: 2, 2: 3}",
+        ];
+        let expected_values = [
+            "[3: c, 1: a, 2: b]",
+            "[z: 1, a: 2, true: 3, m: 4]",
+            "[2: 3, 1: 2]",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            assert_eq!(run(input).to_string(), expected_values[i]);
+        }
+    }
+
+    #[test]
+    fn test_eval_hash_literal_shorthand() {
+        let inputs = [
+            "let a = 1; let b = 2; {a, b}",
+            "let a = 1; let b = 2; deep_equal({a, b}, {\"a\": 1, \"b\": 2})",
+            "let a = 1; {a, \"c\": 3}[\"a\"]",
+            "{missing}",
+        ];
+        let expected_values = ["[a: 1, b: 2]", "true", "1", "identifier not found: missing"];
+
+        for (i, input) in inputs.iter().enumerate() {
+            assert_eq!(run(input).to_string(), expected_values[i]);
+        }
+    }
+
+    #[test]
+    fn test_eval_literal_error_propagation() {
+        let inputs = [
+            "[1, 5 / 0, 3]",
+            "[1, [2, -true]]",
+            "[len(1), 2]",
+            "{\"a\": 1, \"b\": 5 / 0}",
+            "{5 / 0: 1}",
+            "{\"a\": [1, missing]}",
+            "let xs = [1, 5 / 0]; 10",
+        ];
+        let expected_values = [
+            "division by zero",
+            "unknown operator: -BOOLEAN",
+            "argument to 'len' not supported, found INTEGER",
+            "division by zero",
+            "division by zero",
+            "identifier not found: missing",
+            "division by zero",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = Object::Error(expected_values[i].to_string());
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_call_argument_errors() {
+        let inputs = [
+            "let f = fn(a, b) { a + b }; f(1, 5 / 0)",
+            "let f = fn(a, b) { a + b }; f(missing, 2)",
+            "let f = fn(a, b, c) { a }; f(1, 2, -true)",
+            "push([1], len(1))",
+            "len(5 / 0)",
+            "let f = fn(a, b) { a + b }; f(1, 2)",
+        ];
+        let expected_values = [
+            Object::Error("division by zero".to_string()),
+            Object::Error("identifier not found: missing".to_string()),
+            Object::Error("unknown operator: -BOOLEAN".to_string()),
+            Object::Error("argument to 'len' not supported, found INTEGER".to_string()),
+            Object::Error("division by zero".to_string()),
+            Object::Integer(3),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_hash_get() {
+        let inputs = [
+            "hash_get({\"a\": 1}, \"a\", 0)",
+            "hash_get({\"a\": 1}, \"b\", 0)",
+            "hash_get({}, 1, \"none\")",
+            "hash_get({\"a\": 1}, [1], 0)",
+            "hash_get([1], 0, 0)",
+        ];
+        let expected_values = [
+            Object::Integer(1),
+            Object::Integer(0),
+            Object::String("none".to_string()),
+            Object::Error("unusable as hash key: ARRAY".to_string()),
+            Object::Error("first argument to 'hash_get' must be HASH, found ARRAY".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_put() {
+        let inputs = [
+            "let h = {\"a\": 1}; let g = put(h, \"b\", 2); [h[\"b\"], g[\"a\"], g[\"b\"]]",
+            "let h = {\"a\": 1}; let g = put(h, \"a\", 2); [h[\"a\"], g[\"a\"]]",
+            "put({}, fn(x) { x }, 1)",
+            "put([], 1, 1)",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Null),
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Error("unusable as hash key: FUNCTION".to_string()),
+            Object::Error("first argument to 'put' must be HASH, found ARRAY".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_error_handling() {
+        let inputs = [
+            "5 + true;",
+            "5 + true; 5;",
+            "-true",
+            "true * false",
+            "5; true / false; 5",
+            "if (10 > 1) { true + false; }",
+            "foobar",
+            "{\"name\": \"Monkey\"}[fn(x) { x }]",
+            "2 ** -1",
+            "2 ** 70",
+            "1 / 0",
+        ];
+        let expected_values = [
+            "unknown operator: INTEGER + BOOLEAN",
+            "unknown operator: INTEGER + BOOLEAN",
+            "unknown operator: -BOOLEAN",
+            "unknown operator: BOOLEAN * BOOLEAN",
+            "unknown operator: BOOLEAN / BOOLEAN",
+            "unknown operator: BOOLEAN + BOOLEAN",
+            "identifier not found: foobar",
+            "unusable as hash key: FUNCTION",
+            "negative exponent not supported: -1",
+            "integer overflow: 2 ** 70",
+            "division by zero",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = Object::Error(expected_values[i].to_string());
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_error_context() {
+        let inputs = [
+            "let divide = fn(a, b) { a / b }; divide(1, 0);",
+            "let inner = fn() { 1 / 0 }; let outer = fn() { inner() }; outer();",
+            "fn(x) { x + true }(1);",
+            "let count = fn(n) { if (n == 0) { foo } else { count(n - 1) } }; count(3);",
+            "let f = fn(x) { x > 0 }; any([\"a\"], f);",
+            "let f = fn() { 1 / 0 }; try { f() } catch (e) { e }; foo;",
+            "let f = fn() { 1 / 0 }; try { f() } catch (e) { e };",
+        ];
+        let expected_values = [
+            (
+                Object::Error("division by zero".to_string()),
+                vec!["divide"],
+            ),
+            (
+                Object::Error("division by zero".to_string()),
+                vec!["outer", "inner"],
+            ),
+            (
+                Object::Error("unknown operator: INTEGER + BOOLEAN".to_string()),
+                vec!["<anonymous>"],
+            ),
+            (
+                Object::Error("identifier not found: foo".to_string()),
+                vec!["count"],
+            ),
+            (
+                Object::Error("unknown operator: STRING > INTEGER".to_string()),
+                vec!["<anonymous>"],
+            ),
+            (
+                Object::Error("identifier not found: foo".to_string()),
+                vec![],
+            ),
+            (Object::String("division by zero".to_string()), vec![]),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let (expected, context) = &expected_values[i];
+            assert_eq!(*run(input), *expected);
+            assert_eq!(error_context(), *context);
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        assert_eq!(run("1 + 2"), Rc::new(Object::Integer(3)));
+        assert_eq!(
+            run("let x 5;"),
+            Rc::new(Object::Error(
+                "expected =, found 5 (line 1, col 7)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_eval_parsed_program_repeatedly() {
+        let program = parse("let y = x * 2; y + 1;").expect("error occurred while parsing program");
+
+        for (x, expected) in [(1, 3), (10, 21)] {
+            let env = Rc::new(RefCell::new(Environment::new()));
+            env.borrow_mut()
+                .set("x".to_string(), Rc::new(Object::Integer(x)))
+                .expect("error occurred while setting x");
+            assert_eq!(
+                eval(&program, env.clone()),
+                Rc::new(Object::Integer(expected))
+            );
+            assert_eq!(env.borrow().get("y"), Some(Rc::new(Object::Integer(x * 2))));
+        }
+    }
+
+    #[test]
+    fn test_eval_program() {
+        let program = parse_program("let double = fn(x) { x * 2 }; double(n);")
+            .expect("error occurred while parsing program");
+
+        let env = Rc::new(RefCell::new(Environment::new()));
+        env.borrow_mut()
+            .set("n".to_string(), Rc::new(Object::Integer(4)))
+            .expect("error occurred while setting n");
+        assert_eq!(eval_program(&program, env), Rc::new(Object::Integer(8)));
+
+        let env = Rc::new(RefCell::new(Environment::new()));
+        assert_eq!(
+            eval_program(&program, env),
+            Rc::new(Object::Error("identifier not found: n".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_eval_prelude() {
+        let inputs = [
+            "map([1, 2, 3], fn(x) { x * 2 })",
+            "filter([1, 2, 3, 4], fn(x) { x > 2 })",
+            "reduce([1, 2, 3, 4], 0, fn(acc, x) { acc + x })",
+            "map([], fn(x) { x })",
+            "let map = 5; map;",
+            "reduce([1, 2, 3, 4], 0, fn(acc, x) { acc * 10 + x })",
+            "filter([], fn(x) { true })",
+            "reduce([], 7, fn(acc, x) { acc + x })",
+            "len(map(fill(1, 5000), fn(x) { x }))",
+            "len(filter(map(fill(1, 5000), fn(x) { x }), fn(x) { x == 1 }))",
+            "reduce(fill(1, 5000), 0, fn(acc, x) { acc + x })",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(4)),
+                Rc::new(Object::Integer(6)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(3)),
+                Rc::new(Object::Integer(4)),
+            ]),
+            Object::Integer(10),
+            Object::Array(vec![]),
+            Object::Integer(5),
+            Object::Integer(1234),
+            Object::Array(vec![]),
+            Object::Integer(7),
+            Object::Integer(5000),
+            Object::Integer(5000),
+            Object::Integer(5000),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let env = Rc::new(RefCell::new(with_prelude()));
+            let program = parse(input).expect("error occurred while parsing program");
+            assert_eq!(eval(&program, env), Rc::new(expected_values[i].clone()));
+        }
+    }
+
+    #[test]
+    fn test_eval_with_timeout() {
+        let input =
+            "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(30);";
+        let env = Rc::new(RefCell::new(Environment::new()));
+        let program = parse(input).expect("error occurred while parsing program");
+        assert_eq!(
+            eval_with_timeout(&program, env, Duration::from_millis(10)),
+            Rc::new(Object::Error("evaluation timed out".to_string()))
+        );
+
+        let env = Rc::new(RefCell::new(Environment::new()));
+        let program = parse(
+            "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(10);",
+        )
+        .expect("error occurred while parsing program");
+        assert_eq!(
+            eval_with_timeout(&program, env, Duration::from_secs(10)),
+            Rc::new(Object::Integer(55))
+        );
+    }
+
+    #[test]
+    fn test_eval_timeout_is_not_caught() {
+        let inputs = [
+            "let long_loop = fn(n) { if (n < 2) { n } else { long_loop(n - 1) + long_loop(n - 2) } };
+            try { long_loop(30) } catch (e) { 1 }",
+            "let long_loop = fn(n) { if (n < 2) { n } else { long_loop(n - 1) + long_loop(n - 2) } };
+            try { repeat(1, fn(i) { long_loop(30) }) } catch (e) { 1 }",
+        ];
+
+        for input in inputs {
+            let env = Rc::new(RefCell::new(Environment::new()));
+            let program = parse(input).expect("error occurred while parsing program");
+            assert_eq!(
+                eval_with_timeout(&program, env, Duration::from_millis(10)),
+                Rc::new(Object::Error("evaluation timed out".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_each() {
+        let inputs = [
+            "let a = 2; a * 3; puts(a);",
+            "let a = 2; a + true; a;",
+            "1; let f = fn() { return 2; 4 }; f(); 3;",
+        ];
+        let expected_values = [
+            vec![Object::Null, Object::Integer(6), Object::Null],
+            vec![
+                Object::Null,
+                Object::Error("unknown operator: INTEGER + BOOLEAN".to_string()),
+            ],
+            vec![
+                Object::Integer(1),
+                Object::Null,
+                Object::Integer(2),
+                Object::Integer(3),
+            ],
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let env = Rc::new(RefCell::new(Environment::new()));
+            let program = parse_program(input).expect("error occurred while parsing program");
+            let expected = expected_values[i]
+                .iter()
+                .map(|obj| Rc::new(obj.clone()))
+                .collect::<Vec<Rc<Object>>>();
+            assert_eq!(eval_each(&program, env), expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_closures_share_defining_scope() {
+        let inputs = [
+            "let f = fn() { later }; let later = 5; f();",
+            "let x = 1; let f = fn() { let x = 2; x }; f() + x;",
+            "let x = 1; let f = fn() { if (true) { let x = 3; }; x }; f();",
+            "let is_even = fn(n) { if (n == 0) { true } else { is_odd(n - 1) } };
+            let is_odd = fn(n) { if (n == 0) { false } else { is_even(n - 1) } };
+            is_even(10);",
+            "let arr = [1, 2]; arr[5 - 4];",
+            "missing[1 / 0];",
+        ];
+        let expected_values = [
+            Object::Integer(5),
+            Object::Integer(3),
+            Object::Integer(1),
+            Object::Boolean(true),
+            Object::Integer(2),
+            Object::Error("identifier not found: missing".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_large_array_lookups() {
+        let elements = (1..=200)
+            .map(|i| i.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let input = format!(
+            "let arr = [{}];
+            let sum = fn(i) {{ if (i == len(arr)) {{ 0 }} else {{ arr[i] + sum(i + 1) }} }};
+            sum(0);",
+            elements
+        );
+
+        test_evaluating(&input, Object::Integer(20100));
+    }
+
+    #[test]
+    fn test_eval_const_statements() {
+        let inputs = [
+            "const a = 5; a;",
+            "let a = 5; let a = 10; a;",
+            "const a = 5; let a = 10; a;",
+            "const a = 5; const a = 10; a;",
+            "const a = 5; let f = fn() { let a = 10; a }; f();",
+        ];
+        let expected_values = [
+            Object::Integer(5),
+            Object::Integer(10),
+            Object::Error("cannot reassign constant: a".to_string()),
+            Object::Error("cannot reassign constant: a".to_string()),
+            Object::Integer(10),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_do_expressions() {
+        let inputs = [
+            "let y = do { let t = 2; t * 3 }; y;",
+            "let y = do { let t = 2; t * 3 }; t;",
+            "let t = 1; let y = do { let t = 2; t }; t + y;",
+            "do { }",
+            "let f = fn() { do { return 5; }; 10 }; f();",
+        ];
+        let expected_values = [
+            Object::Integer(6),
+            Object::Error("identifier not found: t".to_string()),
+            Object::Integer(3),
+            Object::Null,
+            Object::Integer(5),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_try_expressions() {
+        let inputs = [
+            "try { 1 / 0 } catch (e) { e }",
+            "try { 10 / 2 } catch (e) { e }",
+            "let f = fn(x) { 10 / x }; try { f(0); } catch (err) { \"caught: \" + err }",
+            "try { len(1) } catch (e) { 0 }",
+            "let e = 1; try { 1 / 0 } catch (e) { 2 }; e;",
+            "try { let t = 1; t } catch (e) { e }; t;",
+        ];
+        let expected_values = [
+            Object::String("division by zero".to_string()),
+            Object::Integer(5),
+            Object::String("caught: division by zero".to_string()),
+            Object::Integer(0),
+            Object::Integer(1),
+            Object::Error("identifier not found: t".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_pipe_expressions() {
+        let inputs = [
+            "let double = fn(x) { x * 2 }; 5 |> double",
+            "let double = fn(x) { x * 2 }; double(5) == (5 |> double)",
+            "let sub = fn(a, b) { a - b }; 10 |> sub(3) |> sub(2)",
+            "let double = fn(x) { x * 2 }; 3 |> double |> fn(x) { x + 1 }",
+            "let double = fn(x) { x * 2 }; 1 + 2 |> double",
+            "[1, 2, 3] |> push(4) |> len",
+            "let sub = fn(a, b) { a - b }; 1 |> sub(10, _)",
+            "1 |> fn(x) { x } == 1",
+            "let double = fn(x) { x * 2 }; 2 |> double < 3 |> double",
+        ];
+        let expected_values = [
+            Object::Integer(10),
+            Object::Boolean(true),
+            Object::Integer(5),
+            Object::Integer(7),
+            Object::Integer(6),
+            Object::Integer(4),
+            Object::Integer(9),
+            Object::Boolean(true),
+            Object::Boolean(true),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_partial_application() {
+        let inputs = [
+            "let sub = fn(a, b) { a - b }; let dec = sub(_, 1); dec(5);",
+            "let sub = fn(a, b) { a - b }; let from_ten = sub(10, _); from_ten(3);",
+            "let add = fn(a, b, c) { a + b + c }; let mid = add(1, _, _); mid(2, 3);",
+            "let append = push(_, 4); append([1]);",
+            "let sub = fn(a, b) { a - b }; let dec = sub(_, 1); dec(5, 6);",
+            "let dec = nope(_, 1);",
+            "_",
+        ];
+        let expected_values = [
+            Object::Integer(4),
+            Object::Integer(7),
+            Object::Integer(6),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(4)),
+            ]),
+            Object::Error("wrong number of arguments: expected 1, found 2".to_string()),
+            Object::Error("identifier not found: nope".to_string()),
+            Object::Error("identifier not found: _".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_partial_application_keeps_sandbox() {
+        let env = Rc::new(RefCell::new(Environment::new_sandboxed()));
+        let program = parse("let sub = fn(a, b) { a - b }; sub(_, 1)")
+            .expect("error occurred while parsing program");
+
+        match eval(&program, env).as_ref() {
+            Object::Function { env, .. } => assert!(env.borrow().is_sandboxed()),
+            obj => panic!("expected FUNCTION, found {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_eval_match_expressions() {
+        let inputs = [
+            "match 2 { case 1 => \"one\", case 2 => \"two\", default => \"many\" }",
+            "match 7 { case 1 => \"one\", case 2 => \"two\", default => \"many\" }",
+            "match \"x\" { case \"x\" => 1, case \"y\" => 2 }",
+            "match 3 { case 1 => 1 }",
+            "let x = 4; match x * 2 { case x + 4 => true, default => false }",
+            "match 1 { case 1 => 1 / 0 }",
+            "match 15 { case n if n > 20 => \"big\", case n if n > 10 => \"medium\", default => \"small\" }",
+            "match 25 { case n if n > 20 => \"big\", case n if n > 10 => \"medium\", default => \"small\" }",
+            "match 5 { case n if n > 20 => \"big\", case n if n > 10 => \"medium\", default => \"small\" }",
+            "match 12 { case n if n > 10 => n * 2 }",
+            "let big = false; match 1 { case 1 if big => \"big one\", case 1 => \"one\" }",
+            "let n = 1; match 30 { case n if n > 20 => n }; n;",
+            "let limit = 5; match 3 { case limit if true => \"bound\", default => \"nope\" }",
+            "let limit = 5; match 3 { case limit => \"bound\", default => \"nope\" }",
+            "let limit = 5; match 3 { case limit => limit }",
+            "let limit = 5; match 3 { case n if n == limit => \"same\", default => \"different\" }",
+        ];
+        let expected_values = [
+            Object::String("two".to_string()),
+            Object::String("many".to_string()),
+            Object::Integer(1),
+            Object::Null,
+            Object::Boolean(true),
+            Object::Error("division by zero".to_string()),
+            Object::String("medium".to_string()),
+            Object::String("big".to_string()),
+            Object::String("small".to_string()),
+            Object::Integer(24),
+            Object::String("one".to_string()),
+            Object::Integer(1),
+            Object::String("bound".to_string()),
+            Object::String("bound".to_string()),
+            Object::Integer(3),
+            Object::String("different".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_raise() {
+        let inputs = [
+            "raise(\"boom\")",
+            "raise(\"boom\"); 5;",
+            "try { raise(\"boom\"); 5 } catch (e) { \"caught \" + e }",
+            "let check = fn(x) { if (x < 0) { raise(\"negative\") }; x }; try { check(-1) } catch (e) { e }",
+            "raise(1)",
+        ];
+        let expected_values = [
+            Object::Error("boom".to_string()),
+            Object::Error("boom".to_string()),
+            Object::String("caught boom".to_string()),
+            Object::String("negative".to_string()),
+            Object::Error("argument to 'raise' must be STRING, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_builtin_errors_abort() {
+        let inputs = [
+            "len(1); 5;",
+            "let f = fn() { first(1); 5 }; f();",
+            "let x = push(1, 2); 5;",
+            "try { len(1); 5 } catch (e) { \"caught: \" + e }",
+        ];
+        let expected_values = [
+            Object::Error("argument to 'len' not supported, found INTEGER".to_string()),
+            Object::Error("argument to 'first' must be ARRAY, found INTEGER".to_string()),
+            Object::Error("argument to 'push' must be ARRAY, found INTEGER".to_string()),
+            Object::String("caught: argument to 'len' not supported, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_numeric_conversions() {
+        let inputs = [
+            "float(3)",
+            "float(2.5)",
+            "floor(3.7)",
+            "ceil(3.2)",
+            "round(2.5)",
+            "round(-2.5)",
+            "truncate(-3.7)",
+            "floor(4)",
+            "float(\"3\")",
+            "floor(true)",
+            "round(1e20)",
+        ];
+        let expected_values = [
+            Object::Float(3.0),
+            Object::Float(2.5),
+            Object::Integer(3),
+            Object::Integer(4),
+            Object::Integer(3),
+            Object::Integer(-3),
+            Object::Integer(-3),
+            Object::Integer(4),
+            Object::Error("argument to 'float' must be INTEGER or FLOAT, found STRING".to_string()),
+            Object::Error(
+                "argument to 'floor' must be FLOAT or INTEGER, found BOOLEAN".to_string(),
+            ),
+            Object::Error("argument to 'round' out of INTEGER range: 1e20".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_math_builtins() {
+        let inputs = [
+            "sqrt(9.0)",
+            "sqrt(16)",
+            "pow(2.0, 3.0)",
+            "pow(4, 0.5)",
+            "log(1)",
+            "sin(0)",
+            "cos(0.0)",
+            "sqrt(-1.0)",
+            "log(0)",
+            "pow(2, \"3\")",
+        ];
+        let expected_values = [
+            Object::Float(3.0),
+            Object::Float(4.0),
+            Object::Float(8.0),
+            Object::Float(2.0),
+            Object::Float(0.0),
+            Object::Float(0.0),
+            Object::Float(1.0),
+            Object::Error("argument to 'sqrt' must not be negative, found -1.0".to_string()),
+            Object::Error("argument to 'log' must be positive, found 0.0".to_string()),
+            Object::Error("argument to 'pow' must be FLOAT or INTEGER, found STRING".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_ranges() {
+        let inputs = [
+            "range(5)",
+            "len(range(1000000))",
+            "len(range(10, 0, -3))",
+            "len(range(5, 1))",
+            "range(2, 10, 3)[2]",
+            "range(10, 0, -3)[3]",
+            "range(1000000)[999999]",
+            "range(3)[3]",
+            "range(3)[-1]",
+            "to_array(range(1, 4))",
+            "to_array(range(3, 0, -1))",
+            "count(range(100000), fn(x) { x / 2 * 2 == x })",
+            "range(1, 10, 0)",
+            "range(\"1\")",
+            "range()",
+        ];
+        let expected_values = [
+            Object::Range {
+                start: 0,
+                end: 5,
+                step: 1,
+            },
+            Object::Integer(1000000),
+            Object::Integer(4),
+            Object::Integer(0),
+            Object::Integer(8),
+            Object::Integer(1),
+            Object::Integer(999999),
+            Object::Null,
+            Object::Null,
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(3)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(3)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(1)),
+            ]),
+            Object::Integer(50000),
+            Object::Error("step of 'range' must not be zero".to_string()),
+            Object::Error("arguments to 'range' must be INTEGER, found STRING".to_string()),
+            Object::Error("wrong number of arguments: expected 1 to 3, found 0".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "i64"))]
+    fn test_eval_range_len_overflow() {
+        test_evaluating(
+            "len(range(-2147483647 - 1, 2147483647))",
+            Object::Error("range is too long for 'len'".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_eval_hash_entries() {
+        let pairs = Object::Array(vec![
+            Rc::new(Object::Array(vec![
+                Rc::new(Object::String("a".to_string())),
+                Rc::new(Object::Integer(1)),
+            ])),
+            Rc::new(Object::Array(vec![
+                Rc::new(Object::String("b".to_string())),
+                Rc::new(Object::Integer(2)),
+            ])),
+        ]);
+        let inputs = [
+            "entries({\"a\": 1, \"b\": 2})",
+            "to_array({\"a\": 1, \"b\": 2})",
+            "entries({})",
+            "entries([1, 2])",
+            "to_array(1)",
+        ];
+        let expected_values = [
+            pairs.clone(),
+            pairs,
+            Object::Array(vec![]),
+            Object::Error("argument to 'entries' must be HASH, found ARRAY".to_string()),
+            Object::Error(
+                "argument to 'to_array' must be ARRAY, RANGE or HASH, found INTEGER".to_string(),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_from_entries() {
+        let mut pairs = HashPairs::new();
+        for (key, value) in [("a", 1), ("b", 2)] {
+            let key = Rc::new(Object::String(key.to_string()));
+            pairs.insert(
+                key.get_hash_key().expect("unusable as hash key"),
+                HashPair {
+                    key,
+                    value: Rc::new(Object::Integer(value)),
+                },
+            );
+        }
+        let inputs = [
+            "from_entries([[\"a\", 1], [\"b\", 2]])",
+            "from_entries([[\"a\", 1], [\"b\", 3], [\"b\", 2]])",
+            "from_entries(entries({\"a\": 1, \"b\": 2}))",
+            "from_entries([])",
+            "from_entries([[\"a\", 1, 2]])",
+            "from_entries([1])",
+            "from_entries([[fn(x) { x }, 1]])",
+            "from_entries({})",
+        ];
+        let expected_values = [
+            Object::Hash(pairs.clone()),
+            Object::Hash(pairs.clone()),
+            Object::Hash(pairs),
+            Object::Hash(HashPairs::new()),
+            Object::Error(
+                "entries of 'from_entries' must be [key, value] pairs, found 3 elements"
+                    .to_string(),
+            ),
+            Object::Error("entries of 'from_entries' must be ARRAY, found INTEGER".to_string()),
+            Object::Error("unusable as hash key: FUNCTION".to_string()),
+            Object::Error("argument to 'from_entries' must be ARRAY, found HASH".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_assert_eq() {
+        let inputs = [
+            "assert_eq(1 + 1, 2)",
+            "assert_eq([1, [2, 3]], [1, [2, 3]])",
+            "assert_eq({\"a\": [1]}, {\"a\": [1]})",
+            "assert_eq(1 + 1, 3)",
+            "assert_eq([1, [2, 3]], [1, [2]])",
+            "assert_eq(1, \"1\")",
+            "assert_eq(1, 1); 5",
+            "assert_eq(1, 2); 5",
+        ];
+        let expected_values = [
+            Object::Null,
+            Object::Null,
+            Object::Null,
+            Object::Error("assertion failed: expected 3, found 2".to_string()),
+            Object::Error("assertion failed: expected [1, [2]], found [1, [2, 3]]".to_string()),
+            Object::Error("assertion failed: expected 1 (STRING), found 1 (INTEGER)".to_string()),
+            Object::Integer(5),
+            Object::Error("assertion failed: expected 2, found 1".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_clamp() {
+        let inputs = [
+            "clamp(5, 0, 10)",
+            "clamp(-3, 0, 10)",
+            "clamp(15, 0, 10)",
+            "clamp(4, 4, 4)",
+            "clamp(2.5, 0.0, 1.0)",
+            "clamp(-0.5, 0.0, 1.0)",
+            "clamp(0.25, 0.0, 1.0)",
+            "clamp(5, 10, 0)",
+            "clamp(0.5, 1.0, 0.0)",
+            "clamp(5, 0.0, 10)",
+            "clamp(\"5\", 0, 10)",
+        ];
+        let expected_values = [
+            Object::Integer(5),
+            Object::Integer(0),
+            Object::Integer(10),
+            Object::Integer(4),
+            Object::Float(1.0),
+            Object::Float(0.0),
+            Object::Float(0.25),
+            Object::Error("bounds of 'clamp' must satisfy lo <= hi, found 10 and 0".to_string()),
+            Object::Error("bounds of 'clamp' must satisfy lo <= hi, found 1.0 and 0.0".to_string()),
+            Object::Error(
+                "arguments to 'clamp' must be all INTEGER or all FLOAT, found INTEGER, FLOAT, INTEGER"
+                    .to_string(),
+            ),
+            Object::Error(
+                "arguments to 'clamp' must be all INTEGER or all FLOAT, found STRING, INTEGER, INTEGER"
+                    .to_string(),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_values_of_type() {
+        let inputs = [
+            "values_of_type([1, \"a\", 2, true], \"INTEGER\")",
+            "values_of_type([1, \"a\", 2, true], \"STRING\")",
+            "values_of_type([1, [2], fn(x) { x }], \"ARRAY\")",
+            "values_of_type([1, 2], \"FLOAT\")",
+            "values_of_type([1, 2], \"integer\")",
+            "values_of_type([1, 2], 1)",
+            "values_of_type(1, \"INTEGER\")",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Array(vec![Rc::new(Object::String("a".to_string()))]),
+            Object::Array(vec![Rc::new(Object::Array(vec![Rc::new(
+                Object::Integer(2),
+            )]))]),
+            Object::Array(vec![]),
+            Object::Array(vec![]),
+            Object::Error(
+                "second argument to 'values_of_type' must be STRING, found INTEGER".to_string(),
+            ),
+            Object::Error(
+                "first argument to 'values_of_type' must be ARRAY, found INTEGER".to_string(),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_parse_monkey() {
+        let quote = |node| Object::Quote(Rc::new(node));
+        let inputs = [
+            "parse_monkey(\"1 + 2\")",
+            "parse_monkey(\"let x = 1; x\")",
+            "parse_monkey(\"let x = ;\")",
+            "parse_monkey(1)",
+        ];
+        let expected_values = [
+            quote(Node::Expression(Expression::Infix(InfixExpression {
+                operator: Token::Plus,
+                lhs: Box::new(Expression::Integer(IntegerExpression { value: 1 })),
+                rhs: Box::new(Expression::Integer(IntegerExpression { value: 2 })),
+            }))),
+            quote(parse("let x = 1; x").expect("error occurred while parsing program")),
+            Object::Error(
+                "parse error: no prefix function found for ; (line 1, col 9)".to_string(),
+            ),
+            Object::Error("argument to 'parse_monkey' must be STRING, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+
+        let env = Rc::new(RefCell::new(Environment::new()));
+        let program =
+            parse("parse_monkey(\"1 + 2\")").expect("error occurred while parsing program");
+        assert_eq!(eval(&program, env).to_string(), "QUOTE((1 + 2))");
+    }
+
+    #[test]
+    fn test_eval_eval() {
+        let inputs = [
+            "eval(parse_monkey(\"1 + 2\"))",
+            "eval(\"3 * 4\")",
+            "let x = 5; eval(\"x * 2\")",
+            "eval(\"let y = 1;\"); y",
+            "let f = fn() { eval(\"return 1;\"); 2 }; f()",
+            "\"4 - 1\" |> eval",
+            "apply(eval, [\"x\"])",
+            "eval(\"1 +\")",
+            "eval(1)",
+            "eval(1, 2)",
+        ];
+        let expected_values = [
+            Object::Integer(3),
+            Object::Integer(12),
+            Object::Integer(10),
+            Object::Integer(1),
+            Object::Error("parse error: return outside of function (line 1, col 1)".to_string()),
+            Object::Integer(3),
+            Object::Error("'eval' must be called directly".to_string()),
+            Object::Error(
+                "parse error: no prefix function found for Eof (line 1, col 4)".to_string(),
+            ),
+            Object::Error("argument to 'eval' must be QUOTE or STRING, found INTEGER".to_string()),
+            Object::Error("wrong number of arguments: expected 1, found 2".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_globals_locals() {
+        let inputs = [
+            "let x = 1; let f = fn(y) { let x = 2; locals() }; f(5)",
+            "let x = 1; let f = fn(y) { let x = 2; globals() }; f(5)",
+            "let x = 1; let f = fn(y) { locals()[\"x\"] }; f(5)",
+            "let x = 1; let f = fn(y) { globals()[\"x\"] }; f(5)",
+            "let l = len; locals()",
+            "locals()",
+            "apply(locals, [])",
+            "let g = globals; g()",
+        ];
+        let expected_displays = [
+            "[x: 2, y: 5]",
+            "[f: <function>, x: 1]",
+            "null",
+            "1",
+            "[l: <builtin len>]",
+            "[]",
+            "'locals' must be called directly",
+            "[g: <builtin globals>]",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let env = Rc::new(RefCell::new(Environment::new()));
+            let program = parse(input).expect("error occurred while parsing program");
+            assert_eq!(eval(&program, env).to_string(), expected_displays[i]);
+        }
+    }
+
+    #[test]
+    fn test_eval_is_empty() {
+        let inputs = [
+            "is_empty([])",
+            "is_empty([1])",
+            "is_empty(\"\")",
+            "is_empty(\"a\")",
+            "is_empty({})",
+            "is_empty({\"a\": 1})",
+            "is_empty(range(0))",
+            "is_empty(range(3))",
+            "is_empty(0)",
+            "is_empty(true)",
+        ];
+        let expected_values = [
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Error(
+                "argument to 'is_empty' must be STRING, ARRAY, RANGE or HASH, found INTEGER"
+                    .to_string(),
+            ),
+            Object::Error(
+                "argument to 'is_empty' must be STRING, ARRAY, RANGE or HASH, found BOOLEAN"
+                    .to_string(),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_concat() {
+        let inputs = [
+            "concat([1], [2, 3], [4])",
+            "concat([1, 2])",
+            "concat([], [], [])",
+            "concat()",
+            "concat([1], 2, [3])",
+            "concat([1], \"ab\")",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(3)),
+                Rc::new(Object::Integer(4)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Array(vec![]),
+            Object::Array(vec![]),
+            Object::Error("arguments to 'concat' must be ARRAY, found INTEGER".to_string()),
+            Object::Error("arguments to 'concat' must be ARRAY, found STRING".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_fill() {
+        let inputs = [
+            "fill(0, 3)",
+            "fill(\"x\", 0)",
+            "fill([1], 2)",
+            "fill(0, -1)",
+            "fill(0, \"3\")",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(0)),
+                Rc::new(Object::Integer(0)),
+                Rc::new(Object::Integer(0)),
+            ]),
+            Object::Array(vec![]),
+            Object::Array(vec![
+                Rc::new(Object::Array(vec![Rc::new(Object::Integer(1))])),
+                Rc::new(Object::Array(vec![Rc::new(Object::Integer(1))])),
+            ]),
+            Object::Error("second argument to 'fill' must not be negative, found -1".to_string()),
+            Object::Error("second argument to 'fill' must be INTEGER, found STRING".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_swap() {
+        let inputs = [
+            "swap([1, 2, 3], 0, 2)",
+            "swap([1, 2, 3], 1, 1)",
+            "let a = [1, 2]; swap(a, 0, 1); a",
+            "swap([1, 2, 3], 0, 3)",
+            "swap([1, 2, 3], -1, 0)",
+            "swap([1, 2, 3], 0, \"1\")",
+            "swap(\"ab\", 0, 1)",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(3)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(1)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(3)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Error("index out of range for 'swap': 3 (length 3)".to_string()),
+            Object::Error("index out of range for 'swap': -1 (length 3)".to_string()),
+            Object::Error("indices to 'swap' must be INTEGER, found STRING".to_string()),
+            Object::Error("first argument to 'swap' must be ARRAY, found STRING".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_insert() {
+        let inputs = [
+            "insert([1, 3], 1, 2)",
+            "insert([1, 2], 2, 3)",
+            "insert([], 0, 1)",
+            "let a = [1]; insert(a, 0, 0); a",
+            "insert([1, 2], 3, 3)",
+            "insert([1, 2], -1, 3)",
+            "insert([1, 2], true, 3)",
+            "insert(1, 0, 3)",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(3)),
+            ]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+                Rc::new(Object::Integer(3)),
+            ]),
+            Object::Array(vec![Rc::new(Object::Integer(1))]),
+            Object::Array(vec![Rc::new(Object::Integer(1))]),
+            Object::Error("index out of range for 'insert': 3 (length 2)".to_string()),
+            Object::Error("index out of range for 'insert': -1 (length 2)".to_string()),
+            Object::Error("second argument to 'insert' must be INTEGER, found BOOLEAN".to_string()),
+            Object::Error("first argument to 'insert' must be ARRAY, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_remove_at() {
+        let inputs = [
+            "remove_at([1, 2, 3], 1)",
+            "remove_at([1], 0)",
+            "let a = [1, 2]; remove_at(a, 0); a",
+            "remove_at([1, 2, 3], 3)",
+            "remove_at([1, 2, 3], -1)",
+            "remove_at([], 0)",
+            "remove_at([1], \"0\")",
+            "remove_at({}, 0)",
+        ];
+        let expected_values = [
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(3)),
+            ]),
+            Object::Array(vec![]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(2)),
+            ]),
+            Object::Error("index out of range for 'remove_at': 3 (length 3)".to_string()),
+            Object::Error("index out of range for 'remove_at': -1 (length 3)".to_string()),
+            Object::Error("index out of range for 'remove_at': 0 (length 0)".to_string()),
+            Object::Error(
+                "second argument to 'remove_at' must be INTEGER, found STRING".to_string(),
+            ),
+            Object::Error("first argument to 'remove_at' must be ARRAY, found HASH".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_any_all() {
+        let inputs = [
+            "any([1, 2, 3], fn(x) { x > 2 })",
+            "any([1, 2, 3], fn(x) { x > 3 })",
+            "any([], fn(x) { true })",
+            "all([1, 2, 3], fn(x) { x > 0 })",
+            "all([1, 2, 3], fn(x) { x > 1 })",
+            "all([], fn(x) { false })",
+            "any([1, \"a\"], fn(x) { x > 0 })",
+            "all([0, \"a\"], fn(x) { x > 0 })",
+            "all([1, \"a\"], fn(x) { x > 0 })",
+            "any([0, 1], len)",
+            "all([1], 1)",
+            "any(1, fn(x) { true })",
+        ];
+        let expected_values = [
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Error("unknown operator: STRING > INTEGER".to_string()),
+            Object::Error("argument to 'len' not supported, found INTEGER".to_string()),
+            Object::Error("second argument to 'all' must be FUNCTION, found INTEGER".to_string()),
+            Object::Error("first argument to 'any' must be ARRAY, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_times() {
+        let inputs = [
+            "let f = fn(i) { i * 2 }; times(3, f)",
+            "times(2, fn(i) { i + true })",
+            "times(0, fn(i) { i + true })",
+        ];
+        let expected_values = [
+            Object::Null,
+            Object::Error("unknown operator: INTEGER + BOOLEAN".to_string()),
+            Object::Null,
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_bool() {
+        let inputs = [
+            "bool(0)",
+            "bool(false)",
+            "bool(if (false) { 1 })",
+            "bool(true)",
+            "bool(\"\")",
+            "bool([])",
+            "bool(bool)",
+        ];
+        let expected_values = [true, false, false, true, true, true, true];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = Object::Boolean(expected_values[i]);
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_size_of() {
+        let inputs = [
+            "size_of([1, [2, 3], 4])",
+            "size_of([])",
+            "size_of({\"a\": 1, \"b\": [1, 2]})",
+            "size_of(\"hello\")",
+            "size_of(range(10))",
+            "size_of(42)",
+            "size_of(1.5)",
+            "size_of(true)",
+            "size_of(fn(x) { x })",
+            "size_of(len)",
+        ];
+        let expected_values = [3, 0, 2, 5, 10, 1, 1, 1, 1, 1];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_evaluating(input, Object::Integer(expected_values[i]));
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "i64"))]
+    fn test_eval_size_of_overflow() {
+        test_evaluating(
+            "size_of(range(-2147483647 - 1, 2147483647))",
+            Object::Error("range is too long for 'size_of'".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_eval_sandboxed_environment() {
+        let inputs = [
+            "limit;",
+            "let limit = 20; limit;",
+            "let f = fn() { let limit = 20; }; f();",
+            "let x = limit + 1; x;",
+            "exit(1);",
+            "env_get(\"HOME\");",
+            "env_set(\"HOME\", \"/\");",
+            "eval(\"exit(1)\");",
+            "apply(eval, [\"exit(1)\"]);",
+        ];
+        let expected_values = [
+            Object::Integer(10),
+            Object::Error("cannot reassign protected name: limit".to_string()),
+            Object::Error("cannot reassign protected name: limit".to_string()),
+            Object::Integer(11),
+            Object::Error("builtin not available in sandbox: exit".to_string()),
+            Object::Error("builtin not available in sandbox: env_get".to_string()),
+            Object::Error("builtin not available in sandbox: env_set".to_string()),
+            Object::Error("builtin not available in sandbox: exit".to_string()),
+            Object::Error("'eval' must be called directly".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let env = Rc::new(RefCell::new(Environment::new_sandboxed()));
+            env.borrow_mut()
+                .set_protected("limit".to_string(), Rc::new(Object::Integer(10)))
+                .expect("error occurred while setting protected name");
+
+            let program = parse(input).expect("error occurred while parsing program");
+            assert_eq!(eval(&program, env), Rc::new(expected_values[i].clone()));
+        }
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_eval_sandboxed_file_access() {
+        let inputs = [
+            "read_file(\"notes.txt\");",
+            "write_file(\"notes.txt\", \"\");",
+        ];
+        let expected_values = [
+            Object::Error("builtin not available in sandbox: read_file".to_string()),
+            Object::Error("builtin not available in sandbox: write_file".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let env = Rc::new(RefCell::new(Environment::new_sandboxed()));
+            let program = parse(input).expect("error occurred while parsing program");
+            assert_eq!(eval(&program, env), Rc::new(expected_values[i].clone()));
+        }
+    }
+
+    #[test]
+    fn test_eval_block_scoped_let_statements() {
+        let inputs = [
+            "let x = 1; if (true) { let x = 2; }; x;",
+            "let x = 1; if (true) { let x = 2; x; };",
+            "let x = 1; if (false) { 0 } else { let x = 3; }; x;",
+            "let x = 1; if (true) { let y = x + 1; y; };",
+            "if (true) { let y = 2; }; y;",
+            "let x = 1; let f = fn() { if (true) { let x = 2; }; x }; f();",
+        ];
+        let expected_values = [
+            Object::Integer(1),
+            Object::Integer(2),
+            Object::Integer(1),
+            Object::Integer(2),
+            Object::Error("identifier not found: y".to_string()),
+            Object::Integer(1),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_exit() {
+        let inputs = [
+            "exit(0); 5;",
+            "let x = exit(1); x;",
+            "if (true) { exit(4); }; 5;",
+            "let f = fn() { exit(2); 1; }; f(); 3;",
+            "let f = fn() { let g = fn() { exit(7) }; g(); 1; }; let a = f(); 3;",
+            "exit(true)",
+            "[exit(2), puts(\"in array\")]; 5;",
+            "let f = fn(a, b) { a }; f(exit(3), raise(\"second argument\")); 5;",
+            "if (exit(5)) { 1 }; 6;",
+            "1 + exit(3)",
+            "-exit(6)",
+            "{exit(8): 1}; 5;",
+            "repeat(3, fn(i) { exit(i + 1) }); 5;",
+            "try { exit(9) } catch (e) { 1 }",
+        ];
+        let expected_values = [
+            Object::Exit(0),
+            Object::Exit(1),
+            Object::Exit(4),
+            Object::Exit(2),
+            Object::Exit(7),
+            Object::Error("argument to 'exit' must be INTEGER, found BOOLEAN".to_string()),
+            Object::Exit(2),
+            Object::Exit(3),
+            Object::Exit(5),
+            Object::Exit(3),
+            Object::Exit(6),
+            Object::Exit(8),
+            Object::Exit(1),
+            Object::Exit(9),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = expected_values[i].clone();
+            test_evaluating(input, expected);
+        }
+
+        assert_eq!(run("let a = 1; exit(a + 2); a;"), Rc::new(Object::Exit(3)));
+    }
 }
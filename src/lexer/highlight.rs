@@ -0,0 +1,116 @@
+use std::ops::Range;
+
+use super::{token::Token, Lexer};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SpanKind {
+    Keyword,
+    Identifier,
+    Number,
+    String,
+    Boolean,
+    Operator,
+    Punctuation,
+    Comment,
+    Whitespace,
+    Illegal,
+}
+
+// `range` is in bytes, so hosts can slice the source with it directly.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Span {
+    pub range: Range<usize>,
+    pub kind: SpanKind,
+}
+
+// Splits `input` into consecutive spans that together cover all of it. Whitespace and comments,
+// which the lexer skips, get spans of their own.
+pub fn highlight(input: &str) -> Vec<Span> {
+    // The lexer counts positions in characters.
+    let offsets = input
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain([input.len()])
+        .collect::<Vec<usize>>();
+    let to_offset = |position: usize| offsets[position.min(offsets.len() - 1)];
+
+    let mut spans = Vec::new();
+    let mut lexer = Lexer::new(input);
+    let mut end = 0;
+    loop {
+        let token = lexer.read_token();
+        let start = to_offset(lexer.token_offset);
+        push_trivia(&mut spans, input, end..start);
+        if token == Token::Eof {
+            break;
+        }
+
+        end = to_offset(lexer.position);
+        spans.push(Span {
+            range: start..end,
+            kind: span_kind(&token),
+        });
+    }
+    spans
+}
+
+fn push_trivia(spans: &mut Vec<Span>, input: &str, range: Range<usize>) {
+    let mut start = range.start;
+    while start < range.end {
+        let rest = &input[start..range.end];
+        let (len, kind) = if rest.starts_with("//") {
+            (rest.find('\n').unwrap_or(rest.len()), SpanKind::Comment)
+        } else {
+            (rest.find("//").unwrap_or(rest.len()), SpanKind::Whitespace)
+        };
+        spans.push(Span {
+            range: start..start + len,
+            kind,
+        });
+        start += len;
+    }
+}
+
+fn span_kind(token: &Token) -> SpanKind {
+    match token {
+        Token::Let
+        | Token::Const
+        | Token::Function
+        | Token::If
+        | Token::Else
+        | Token::Return
+        | Token::Do
+        | Token::Try
+        | Token::Catch
+        | Token::Match
+        | Token::Case
+        | Token::Default => SpanKind::Keyword,
+        Token::Identifier(_) => SpanKind::Identifier,
+        Token::Integer(_) | Token::Float(_) => SpanKind::Number,
+        Token::String(_) | Token::Char(_) => SpanKind::String,
+        Token::Boolean(_) => SpanKind::Boolean,
+        Token::Assign
+        | Token::Plus
+        | Token::Minus
+        | Token::Bang
+        | Token::Asterisk
+        | Token::Slash
+        | Token::Power
+        | Token::LessThan
+        | Token::GreaterThan
+        | Token::Equal
+        | Token::NotEqual
+        | Token::FatArrow
+        | Token::Pipe => SpanKind::Operator,
+        Token::Comma
+        | Token::Semicolon
+        | Token::Colon
+        | Token::LParen
+        | Token::RParen
+        | Token::LBrace
+        | Token::RBrace
+        | Token::LBracket
+        | Token::RBracket => SpanKind::Punctuation,
+        Token::Unknown | Token::Illegal(_) | Token::Eof => SpanKind::Illegal,
+    }
+}
@@ -1,13 +1,24 @@
-use token::{match_identifier, Token};
+use token::{match_identifier, Position, Token};
 
+pub mod highlight;
+mod test_highlight;
 mod test_lexer;
 pub mod token;
 
+pub fn tokenize(input: &str) -> Vec<Token> {
+    Lexer::new(input).tokenize()
+}
+
 pub struct Lexer {
     input: String,
     position: usize,
     read_position: usize,
     character: char,
+    line: usize,
+    column: usize,
+    token_position: Position,
+    token_offset: usize,
+    peeked: Option<(Token, Position)>,
 }
 
 impl Lexer {
@@ -17,6 +28,11 @@ impl Lexer {
             position: 0,
             read_position: 0,
             character: '\0',
+            line: 1,
+            column: 0,
+            token_position: Position::default(),
+            token_offset: 0,
+            peeked: None,
         };
 
         lexer.read_char();
@@ -36,15 +52,50 @@ impl Lexer {
     }
 
     pub fn next_token(&mut self) -> Token {
+        match self.peeked.take() {
+            Some((token, position)) => {
+                self.token_position = position;
+                token
+            }
+            None => self.read_token(),
+        }
+    }
+
+    // Reads ahead one token, which the next call to `next_token` returns. `token_position` keeps
+    // reporting the position of the last token returned until then.
+    pub fn peek_token(&mut self) -> &Token {
+        let peeked = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => {
+                let position = self.token_position;
+                let token = self.read_token();
+                let peeked = (token, self.token_position);
+                self.token_position = position;
+                peeked
+            }
+        };
+        &self.peeked.insert(peeked).0
+    }
+
+    fn read_token(&mut self) -> Token {
         let mut skip_read_char = false;
 
         self.skip_whitespace();
 
+        self.token_position = Position {
+            line: self.line,
+            column: self.column,
+        };
+        self.token_offset = self.position;
+
         let token = match self.character {
             '=' => {
                 if self.peek_char() == '=' {
                     self.read_char();
                     Token::Equal
+                } else if self.peek_char() == '>' {
+                    self.read_char();
+                    Token::FatArrow
                 } else {
                     Token::Assign
                 }
@@ -60,7 +111,18 @@ impl Lexer {
                 }
             }
             '/' => Token::Slash,
-            '*' => Token::Asterisk,
+            '*' => {
+                if self.peek_char() == '*' {
+                    self.read_char();
+                    Token::Power
+                } else {
+                    Token::Asterisk
+                }
+            }
+            '|' if self.peek_char() == '>' => {
+                self.read_char();
+                Token::Pipe
+            }
             '<' => Token::LessThan,
             '>' => Token::GreaterThan,
             ',' => Token::Comma,
@@ -73,27 +135,21 @@ impl Lexer {
             '[' => Token::LBracket,
             ']' => Token::RBracket,
             '"' => Token::String(self.read_string()),
+            '\'' => self.read_char_literal(),
             '\0' => Token::Eof,
-            ch if ch.is_ascii_alphabetic() => {
+            ch if is_letter(ch) => {
                 let position = self.position;
-                while self.character.is_ascii_alphabetic() {
+                while is_letter(self.character) {
                     self.read_char();
                 }
                 skip_read_char = true;
                 match_identifier(&self.input[position..self.position])
             }
             ch if ch.is_ascii_digit() => {
-                let position = self.position;
-                while self.character.is_ascii_digit() {
-                    self.read_char();
-                }
                 skip_read_char = true;
-                match self.input[position..self.position].parse() {
-                    Ok(integer) => Token::Integer(integer),
-                    Err(_) => Token::Unknown,
-                }
+                self.read_number()
             }
-            _ => Token::Unknown,
+            ch => Token::Illegal(format!("illegal character: {}", ch)),
         };
 
         if !skip_read_char {
@@ -102,6 +158,10 @@ impl Lexer {
         token
     }
 
+    pub fn token_position(&self) -> Position {
+        self.token_position
+    }
+
     fn read_string(&mut self) -> String {
         let position = self.position + 1;
         loop {
@@ -113,7 +173,99 @@ impl Lexer {
         self.input[position..self.position].to_string()
     }
 
+    fn read_number(&mut self) -> Token {
+        let position = self.position;
+        let mut is_float = false;
+        self.read_digits();
+
+        if self.character == '.' && self.peek_char().is_ascii_digit() {
+            is_float = true;
+            self.read_char();
+            self.read_digits();
+        }
+
+        if matches!(self.character, 'e' | 'E') {
+            is_float = true;
+            self.read_char();
+            if matches!(self.character, '+' | '-') {
+                self.read_char();
+            }
+            if !self.character.is_ascii_digit() {
+                return Token::Illegal(format!(
+                    "invalid number literal: {}",
+                    &self.input[position..self.position]
+                ));
+            }
+            self.read_digits();
+        }
+
+        let literal = &self.input[position..self.position];
+        if is_float {
+            match literal.parse() {
+                Ok(float) => Token::Float(float),
+                Err(_) => Token::Illegal(format!("invalid number literal: {}", literal)),
+            }
+        } else {
+            match literal.parse() {
+                Ok(integer) => Token::Integer(integer),
+                Err(_) => Token::Illegal(format!("integer literal out of range: {}", literal)),
+            }
+        }
+    }
+
+    fn read_digits(&mut self) {
+        while self.character.is_ascii_digit() {
+            self.read_char();
+        }
+    }
+
+    fn read_char_literal(&mut self) -> Token {
+        let start = self.position;
+
+        self.read_char();
+        let ch = match self.character {
+            '\'' => return Token::Illegal("empty character literal".to_string()),
+            '\0' | '\n' => return Token::Illegal("unterminated character literal".to_string()),
+            '\\' => {
+                self.read_char();
+                match self.character {
+                    'n' => Some('\n'),
+                    't' => Some('\t'),
+                    'r' => Some('\r'),
+                    '0' => Some('\0'),
+                    '\\' => Some('\\'),
+                    '\'' => Some('\''),
+                    '"' => Some('"'),
+                    _ => None,
+                }
+            }
+            ch => Some(ch),
+        };
+
+        self.read_char();
+        if let (Some(ch), '\'') = (ch, self.character) {
+            return Token::Char(ch);
+        }
+
+        while !matches!(self.character, '\'' | '\0' | '\n') {
+            self.read_char();
+        }
+        match self.character {
+            '\'' => Token::Illegal(format!(
+                "invalid character literal: {}",
+                &self.input[start..=self.position]
+            )),
+            _ => Token::Illegal("unterminated character literal".to_string()),
+        }
+    }
+
     fn read_char(&mut self) {
+        if self.character == '\n' {
+            self.line += 1;
+            self.column = 0;
+        }
+        self.column += 1;
+
         self.character = self.peek_char();
         self.position = self.read_position;
         self.read_position += 1;
@@ -123,9 +275,33 @@ impl Lexer {
         self.input.chars().nth(self.read_position).unwrap_or('\0')
     }
 
+    // Line comments start with `//` and count as whitespace up to the end of the line.
     fn skip_whitespace(&mut self) {
-        while self.character.is_ascii_whitespace() {
-            self.read_char();
+        loop {
+            if self.character.is_ascii_whitespace() {
+                self.read_char();
+            } else if self.character == '/' && self.peek_char() == '/' {
+                while self.character != '\n' && self.character != '\0' {
+                    self.read_char();
+                }
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl Iterator for Lexer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_token() {
+            Token::Eof => None,
+            token => Some(token),
         }
     }
 }
+
+fn is_letter(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
+}
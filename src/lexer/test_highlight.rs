@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use crate::lexer::highlight::{highlight, Span, SpanKind};
+
+    fn test_highlighting(input: &str, expected: &[(&str, SpanKind)]) {
+        let spans = highlight(input);
+
+        let texts = spans
+            .iter()
+            .map(|span| (&input[span.range.clone()], span.kind))
+            .collect::<Vec<(&str, SpanKind)>>();
+        assert_eq!(texts, expected, "input: {}", input);
+
+        let mut end = 0;
+        for Span { range, .. } in spans.iter() {
+            assert_eq!(range.start, end, "gap before {:?}", range);
+            end = range.end;
+        }
+        assert_eq!(end, input.len());
+    }
+
+    #[test]
+    fn test_highlight() {
+        test_highlighting(
+            "let x = 5; // five\nputs(\"hi\");",
+            &[
+                ("let", SpanKind::Keyword),
+                (" ", SpanKind::Whitespace),
+                ("x", SpanKind::Identifier),
+                (" ", SpanKind::Whitespace),
+                ("=", SpanKind::Operator),
+                (" ", SpanKind::Whitespace),
+                ("5", SpanKind::Number),
+                (";", SpanKind::Punctuation),
+                (" ", SpanKind::Whitespace),
+                ("// five", SpanKind::Comment),
+                ("\n", SpanKind::Whitespace),
+                ("puts", SpanKind::Identifier),
+                ("(", SpanKind::Punctuation),
+                ("\"hi\"", SpanKind::String),
+                (")", SpanKind::Punctuation),
+                (";", SpanKind::Punctuation),
+            ],
+        );
+        test_highlighting(
+            "if (true) { 1.5 ** x } // a\n// b",
+            &[
+                ("if", SpanKind::Keyword),
+                (" ", SpanKind::Whitespace),
+                ("(", SpanKind::Punctuation),
+                ("true", SpanKind::Boolean),
+                (")", SpanKind::Punctuation),
+                (" ", SpanKind::Whitespace),
+                ("{", SpanKind::Punctuation),
+                (" ", SpanKind::Whitespace),
+                ("1.5", SpanKind::Number),
+                (" ", SpanKind::Whitespace),
+                ("**", SpanKind::Operator),
+                (" ", SpanKind::Whitespace),
+                ("x", SpanKind::Identifier),
+                (" ", SpanKind::Whitespace),
+                ("}", SpanKind::Punctuation),
+                (" ", SpanKind::Whitespace),
+                ("// a", SpanKind::Comment),
+                ("\n", SpanKind::Whitespace),
+                ("// b", SpanKind::Comment),
+            ],
+        );
+        test_highlighting(
+            "x |> f @ \"open",
+            &[
+                ("x", SpanKind::Identifier),
+                (" ", SpanKind::Whitespace),
+                ("|>", SpanKind::Operator),
+                (" ", SpanKind::Whitespace),
+                ("f", SpanKind::Identifier),
+                (" ", SpanKind::Whitespace),
+                ("@", SpanKind::Illegal),
+                (" ", SpanKind::Whitespace),
+                ("\"open", SpanKind::String),
+            ],
+        );
+        test_highlighting("", &[]);
+        test_highlighting("  ", &[("  ", SpanKind::Whitespace)]);
+    }
+}
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use crate::lexer::{token::Token, Lexer};
+    use crate::lexer::{
+        token::{Position, Token},
+        tokenize, Lexer,
+    };
 
     fn test_lexing(input: &str, expected: &[Token]) {
         let lexer = Lexer::new(input);
@@ -27,6 +30,34 @@ let ten = 10;";
         test_lexing(input, &expected);
     }
 
+    #[test]
+    fn test_lexer_const() {
+        let input = "const five = 5;";
+        let expected = vec![
+            Token::Const,
+            Token::Identifier("five".to_string()),
+            Token::Assign,
+            Token::Integer(5),
+            Token::Semicolon,
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
+    #[test]
+    fn test_lexer_underscore_identifier() {
+        let input = "let deep_equal = _x;";
+        let expected = vec![
+            Token::Let,
+            Token::Identifier("deep_equal".to_string()),
+            Token::Assign,
+            Token::Identifier("_x".to_string()),
+            Token::Semicolon,
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
     #[test]
     fn test_lexer_function() {
         let input = "let add = fn(x, y) {
@@ -129,6 +160,95 @@ let result = add(five, ten);";
         test_lexing(input, &expected);
     }
 
+    #[test]
+    fn test_lexer_match() {
+        let input = "match x { case 1 => a, default => b }";
+        let expected = vec![
+            Token::Match,
+            Token::Identifier("x".to_string()),
+            Token::LBrace,
+            Token::Case,
+            Token::Integer(1),
+            Token::FatArrow,
+            Token::Identifier("a".to_string()),
+            Token::Comma,
+            Token::Default,
+            Token::FatArrow,
+            Token::Identifier("b".to_string()),
+            Token::RBrace,
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
+    #[test]
+    fn test_lexer_pipe() {
+        let input = "x |> f | y";
+        let expected = vec![
+            Token::Identifier("x".to_string()),
+            Token::Pipe,
+            Token::Identifier("f".to_string()),
+            Token::Illegal("illegal character: |".to_string()),
+            Token::Identifier("y".to_string()),
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
+    #[test]
+    fn test_lexer_peek_token() {
+        let mut lexer = Lexer::new("x == y;\nz");
+
+        assert_eq!(lexer.peek_token(), &Token::Identifier("x".to_string()));
+        assert_eq!(lexer.peek_token(), &Token::Identifier("x".to_string()));
+        assert_eq!(lexer.next_token(), Token::Identifier("x".to_string()));
+
+        assert_eq!(lexer.peek_token(), &Token::Equal);
+        assert_eq!(lexer.token_position(), Position { line: 1, column: 1 });
+        assert_eq!(lexer.next_token(), Token::Equal);
+        assert_eq!(lexer.token_position(), Position { line: 1, column: 3 });
+
+        assert_eq!(lexer.next_token(), Token::Identifier("y".to_string()));
+        assert_eq!(lexer.next_token(), Token::Semicolon);
+        assert_eq!(lexer.peek_token(), &Token::Identifier("z".to_string()));
+        assert_eq!(lexer.next_token(), Token::Identifier("z".to_string()));
+        assert_eq!(lexer.token_position(), Position { line: 2, column: 1 });
+        assert_eq!(lexer.peek_token(), &Token::Eof);
+        assert_eq!(lexer.next_token(), Token::Eof);
+    }
+
+    #[test]
+    fn test_lexer_whitespace_and_comments() {
+        let inputs = [
+            "",
+            "  \n\t \r\n",
+            "// only a comment",
+            "// first\n  // second\n",
+            "let x = 4 / 2; // halve\nx // trailing",
+        ];
+        let expected = [
+            vec![Token::Eof],
+            vec![Token::Eof],
+            vec![Token::Eof],
+            vec![Token::Eof],
+            vec![
+                Token::Let,
+                Token::Identifier("x".to_string()),
+                Token::Assign,
+                Token::Integer(4),
+                Token::Slash,
+                Token::Integer(2),
+                Token::Semicolon,
+                Token::Identifier("x".to_string()),
+                Token::Eof,
+            ],
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_lexing(input, &expected[i]);
+        }
+    }
+
     #[test]
     fn test_lexer_string() {
         let input = "\"foobar\";
@@ -143,6 +263,36 @@ let result = add(five, ten);";
         test_lexing(input, &expected);
     }
 
+    #[test]
+    fn test_lexer_char() {
+        let input = "'a' '\\n' '\\'' 'ab' '' 'x";
+        let expected = vec![
+            Token::Char('a'),
+            Token::Char('\n'),
+            Token::Char('\''),
+            Token::Illegal("invalid character literal: 'ab'".to_string()),
+            Token::Illegal("empty character literal".to_string()),
+            Token::Illegal("unterminated character literal".to_string()),
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
+    #[test]
+    fn test_lexer_float() {
+        let input = "1.25 1e3 2.5e-4 6.02E23 1e 1.5e+";
+        let expected = vec![
+            Token::Float(1.25),
+            Token::Float(1000.0),
+            Token::Float(0.00025),
+            Token::Float(6.02e23),
+            Token::Illegal("invalid number literal: 1e".to_string()),
+            Token::Illegal("invalid number literal: 1.5e+".to_string()),
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
     #[test]
     fn test_lexer_array() {
         let input = "[1, 2];";
@@ -172,4 +322,74 @@ let result = add(five, ten);";
         ];
         test_lexing(input, &expected);
     }
+
+    #[test]
+    fn test_lexer_illegal_character() {
+        let input = "let x = 5 @ 3; x # 99999999999999999999;";
+        let expected = vec![
+            Token::Let,
+            Token::Identifier("x".to_string()),
+            Token::Assign,
+            Token::Integer(5),
+            Token::Illegal("illegal character: @".to_string()),
+            Token::Integer(3),
+            Token::Semicolon,
+            Token::Identifier("x".to_string()),
+            Token::Illegal("illegal character: #".to_string()),
+            Token::Illegal("integer literal out of range: 99999999999999999999".to_string()),
+            Token::Semicolon,
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_lexer_wide_integer() {
+        let input = "2147483648 9223372036854775807";
+        let expected = vec![
+            Token::Integer(2147483648),
+            Token::Integer(9223372036854775807),
+            Token::Eof,
+        ];
+        test_lexing(input, &expected);
+    }
+
+    #[test]
+    fn test_lexer_iterator() {
+        let input = "let add = fn(x, y) { x + y; };";
+        let expected = vec![
+            Token::Let,
+            Token::Identifier("add".to_string()),
+            Token::Assign,
+            Token::Function,
+            Token::LParen,
+            Token::Identifier("x".to_string()),
+            Token::Comma,
+            Token::Identifier("y".to_string()),
+            Token::RParen,
+            Token::LBrace,
+            Token::Identifier("x".to_string()),
+            Token::Plus,
+            Token::Identifier("y".to_string()),
+            Token::Semicolon,
+            Token::RBrace,
+            Token::Semicolon,
+        ];
+        assert_eq!(Lexer::new(input).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let input = "let x = 5;";
+        let expected = vec![
+            Token::Let,
+            Token::Identifier("x".to_string()),
+            Token::Assign,
+            Token::Integer(5),
+            Token::Semicolon,
+            Token::Eof,
+        ];
+        assert_eq!(tokenize(input), expected);
+    }
 }
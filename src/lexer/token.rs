@@ -1,13 +1,32 @@
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+use std::fmt::{Display, Formatter, Result};
+
+use crate::object::Integer;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "line {}, col {}", self.line, self.column)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum Token {
     Unknown,
+    Illegal(String),
     Eof,
 
     // Identifiers
     Identifier(String),
-    Integer(i32),
+    Integer(Integer),
+    Float(f64),
     Boolean(bool),
     String(String),
+    Char(char),
 
     // Operators
     Assign,
@@ -16,12 +35,15 @@ pub enum Token {
     Bang,
     Asterisk,
     Slash,
+    Power,
 
     LessThan,
     GreaterThan,
 
     Equal,
     NotEqual,
+    FatArrow,
+    Pipe,
 
     // Delimiters
     Comma,
@@ -37,10 +59,17 @@ pub enum Token {
 
     // Keywords
     Let,
+    Const,
     Function,
     If,
     Else,
     Return,
+    Do,
+    Try,
+    Catch,
+    Match,
+    Case,
+    Default,
 }
 
 impl Token {
@@ -48,18 +77,24 @@ impl Token {
         match self {
             Self::Identifier(identifier) => identifier.to_owned(),
             Self::Integer(integer) => integer.to_string(),
+            Self::Float(float) => format!("{:?}", float),
             Self::Boolean(boolean) => boolean.to_string(),
             Self::String(string) => string.to_owned(),
+            Self::Char(ch) => ch.to_string(),
+            Self::Illegal(message) => message.to_owned(),
             Self::Assign => '='.to_string(),
             Self::Plus => '+'.to_string(),
             Self::Minus => '-'.to_string(),
             Self::Bang => '!'.to_string(),
             Self::Asterisk => '*'.to_string(),
             Self::Slash => '/'.to_string(),
+            Self::Power => "**".to_string(),
             Self::LessThan => '<'.to_string(),
             Self::GreaterThan => '>'.to_string(),
             Self::Equal => "==".to_string(),
             Self::NotEqual => "!=".to_string(),
+            Self::FatArrow => "=>".to_string(),
+            Self::Pipe => "|>".to_string(),
             Self::Comma => ','.to_string(),
             Self::Semicolon => ';'.to_string(),
             Self::Colon => ':'.to_string(),
@@ -70,10 +105,17 @@ impl Token {
             Self::LBracket => '['.to_string(),
             Self::RBracket => ']'.to_string(),
             Self::Let => "let".to_string(),
+            Self::Const => "const".to_string(),
             Self::Function => "fn".to_string(),
             Self::If => "if".to_string(),
             Self::Else => "else".to_string(),
             Self::Return => "return".to_string(),
+            Self::Do => "do".to_string(),
+            Self::Try => "try".to_string(),
+            Self::Catch => "catch".to_string(),
+            Self::Match => "match".to_string(),
+            Self::Case => "case".to_string(),
+            Self::Default => "default".to_string(),
             Self::Eof => "Eof".to_string(),
             _ => '\0'.to_string(),
         }
@@ -83,12 +125,19 @@ impl Token {
 pub fn match_identifier(identifier: &str) -> Token {
     match identifier {
         "let" => Token::Let,
+        "const" => Token::Const,
         "fn" => Token::Function,
         "true" => Token::Boolean(true),
         "false" => Token::Boolean(false),
         "if" => Token::If,
         "else" => Token::Else,
         "return" => Token::Return,
+        "do" => Token::Do,
+        "try" => Token::Try,
+        "catch" => Token::Catch,
+        "match" => Token::Match,
+        "case" => Token::Case,
+        "default" => Token::Default,
         _ => Token::Identifier(identifier.to_string()),
     }
 }
@@ -4,4 +4,5 @@ pub mod evaluator;
 pub mod lexer;
 pub mod object;
 pub mod parser;
+pub mod repl;
 pub mod vm;
@@ -1,136 +1,1741 @@
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    env,
+    rc::Rc,
+    slice,
+    sync::OnceLock,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use super::{new_error, Object};
+use super::{
+    deep_equal, is_truthy, new_error, range_len, range_values, HashKey, HashPair, HashPairs,
+    Integer, Object,
+};
+use crate::{
+    evaluator::{apply_callback, environment::Environment},
+    parser::{
+        ast::{
+            BlockStatement, CallExpression, Expression, ExpressionStatement, IdentifierExpression,
+            Node, Statement,
+        },
+        parse_program,
+    },
+};
 
 pub type BuiltinFn = fn(&[Rc<Object>]) -> Rc<Object>;
 
-pub fn get_builtin_fn(name: &str) -> Option<BuiltinFn> {
-    match name {
-        "len" => Some(LEN_BUILTIN),
-        "first" => Some(FIRST_BUILTIN),
-        "last" => Some(LAST_BUILTIN),
-        "rest" => Some(REST_BUILTIN),
-        "push" => Some(PUSH_BUILTIN),
-        "puts" => Some(PUTS_BUILTIN),
-        _ => None,
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Debug, PartialEq)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: Option<usize>,
+    pub description: &'static str,
+    pub function: BuiltinFn,
+}
+
+impl Builtin {
+    const fn new(
+        name: &'static str,
+        arity: Option<usize>,
+        description: &'static str,
+        function: BuiltinFn,
+    ) -> Self {
+        Self {
+            name,
+            arity,
+            description,
+            function,
+        }
+    }
+
+    pub fn call(&self, args: &[Rc<Object>]) -> Rc<Object> {
+        match self.arity {
+            Some(arity) if arity != args.len() => Rc::new(new_error(format!(
+                "wrong number of arguments: expected {}, found {}",
+                arity,
+                args.len()
+            ))),
+            _ => (self.function)(args),
+        }
     }
 }
 
-pub static BUILTINS: [(&str, BuiltinFn); 6] = [
-    ("len", LEN_BUILTIN),
-    ("first", FIRST_BUILTIN),
-    ("last", LAST_BUILTIN),
-    ("rest", REST_BUILTIN),
-    ("push", PUSH_BUILTIN),
-    ("puts", PUTS_BUILTIN),
+static REGISTRY: OnceLock<HashMap<&'static str, &'static Builtin>> = OnceLock::new();
+
+pub fn get_builtin_fn(name: &str) -> Option<&'static Builtin> {
+    REGISTRY
+        .get_or_init(|| {
+            BUILTINS
+                .iter()
+                .map(|builtin| (builtin.name, builtin))
+                .collect()
+        })
+        .get(name)
+        .copied()
+}
+
+pub fn builtin_names() -> Vec<&'static str> {
+    BUILTINS.iter().map(|builtin| builtin.name).collect()
+}
+
+const BUILTIN_COUNT: usize = if cfg!(feature = "io") { 68 } else { 66 };
+
+pub static BUILTINS: [Builtin; BUILTIN_COUNT] = [
+    Builtin::new(
+        "len",
+        Some(1),
+        "Returns the length of a string, array or range.",
+        LEN_BUILTIN,
+    ),
+    Builtin::new(
+        "first",
+        Some(1),
+        "Returns the first element of an array, or null if it is empty.",
+        FIRST_BUILTIN,
+    ),
+    Builtin::new(
+        "last",
+        Some(1),
+        "Returns the last element of an array, or null if it is empty.",
+        LAST_BUILTIN,
+    ),
+    Builtin::new(
+        "rest",
+        Some(1),
+        "Returns a new array with every element but the first.",
+        REST_BUILTIN,
+    ),
+    Builtin::new(
+        "push",
+        Some(2),
+        "Returns a new array with the value appended.",
+        PUSH_BUILTIN,
+    ),
+    Builtin::new(
+        "puts",
+        None,
+        "Prints its arguments separated by spaces, followed by a newline.",
+        PUTS_BUILTIN,
+    ),
+    Builtin::new(
+        "exit",
+        Some(1),
+        "Stops the program with the given exit code.",
+        EXIT_BUILTIN,
+    ),
+    Builtin::new(
+        "deep_equal",
+        Some(2),
+        "Compares two values structurally, including nested arrays and hashes.",
+        DEEP_EQUAL_BUILTIN,
+    ),
+    Builtin::new(
+        "apply",
+        Some(2),
+        "Calls a function with the elements of an array as its arguments.",
+        APPLY_BUILTIN,
+    ),
+    Builtin::new(
+        "compose",
+        Some(2),
+        "Returns a function computing f(g(x)).",
+        COMPOSE_BUILTIN,
+    ),
+    Builtin::new(
+        "repeat",
+        Some(2),
+        "Calls a function with each index below n and collects the results.",
+        REPEAT_BUILTIN,
+    ),
+    Builtin::new(
+        "zip",
+        Some(2),
+        "Pairs up the elements of two arrays, stopping at the shorter one.",
+        ZIP_BUILTIN,
+    ),
+    Builtin::new(
+        "unique",
+        Some(1),
+        "Returns an array without duplicate elements, keeping first occurrences.",
+        UNIQUE_BUILTIN,
+    ),
+    Builtin::new(
+        "count",
+        Some(2),
+        "Counts the elements of an array or range for which the predicate is truthy.",
+        COUNT_BUILTIN,
+    ),
+    Builtin::new(
+        "take",
+        Some(2),
+        "Returns the first n elements of an array.",
+        TAKE_BUILTIN,
+    ),
+    Builtin::new(
+        "drop",
+        Some(2),
+        "Returns an array without its first n elements.",
+        DROP_BUILTIN,
+    ),
+    Builtin::new(
+        "pad_start",
+        Some(3),
+        "Pads the start of a string with a character up to a width.",
+        PAD_START_BUILTIN,
+    ),
+    Builtin::new(
+        "pad_end",
+        Some(3),
+        "Pads the end of a string with a character up to a width.",
+        PAD_END_BUILTIN,
+    ),
+    Builtin::new(
+        "hash_get",
+        Some(3),
+        "Returns the value for a key in a hash, or the default if it is absent.",
+        HASH_GET_BUILTIN,
+    ),
+    Builtin::new(
+        "put",
+        Some(3),
+        "Returns a new hash with the key set to the value.",
+        PUT_BUILTIN,
+    ),
+    Builtin::new(
+        "min_by",
+        Some(2),
+        "Returns the element with the smallest key.",
+        MIN_BY_BUILTIN,
+    ),
+    Builtin::new(
+        "max_by",
+        Some(2),
+        "Returns the element with the largest key.",
+        MAX_BY_BUILTIN,
+    ),
+    Builtin::new(
+        "raise",
+        Some(1),
+        "Raises an error with the given message.",
+        RAISE_BUILTIN,
+    ),
+    Builtin::new(
+        "help",
+        Some(1),
+        "Returns the description of a builtin.",
+        HELP_BUILTIN,
+    ),
+    Builtin::new(
+        "float",
+        Some(1),
+        "Converts a number to a float.",
+        FLOAT_BUILTIN,
+    ),
+    Builtin::new(
+        "floor",
+        Some(1),
+        "Rounds a float down to an integer.",
+        FLOOR_BUILTIN,
+    ),
+    Builtin::new(
+        "ceil",
+        Some(1),
+        "Rounds a float up to an integer.",
+        CEIL_BUILTIN,
+    ),
+    Builtin::new(
+        "round",
+        Some(1),
+        "Rounds a float to the nearest integer.",
+        ROUND_BUILTIN,
+    ),
+    Builtin::new(
+        "truncate",
+        Some(1),
+        "Drops the fractional part of a float.",
+        TRUNCATE_BUILTIN,
+    ),
+    Builtin::new(
+        "sqrt",
+        Some(1),
+        "Returns the square root of a number.",
+        SQRT_BUILTIN,
+    ),
+    Builtin::new(
+        "pow",
+        Some(2),
+        "Raises a base to a float exponent.",
+        POW_BUILTIN,
+    ),
+    Builtin::new(
+        "log",
+        Some(1),
+        "Returns the natural logarithm of a number.",
+        LOG_BUILTIN,
+    ),
+    Builtin::new(
+        "sin",
+        Some(1),
+        "Returns the sine of an angle in radians.",
+        SIN_BUILTIN,
+    ),
+    Builtin::new(
+        "cos",
+        Some(1),
+        "Returns the cosine of an angle in radians.",
+        COS_BUILTIN,
+    ),
+    Builtin::new(
+        "time_now",
+        Some(0),
+        "Returns the current Unix time in milliseconds.",
+        TIME_NOW_BUILTIN,
+    ),
+    Builtin::new(
+        "sleep",
+        Some(1),
+        "Pauses evaluation for the given number of milliseconds.",
+        SLEEP_BUILTIN,
+    ),
+    Builtin::new(
+        "range",
+        None,
+        "Returns a lazy range of integers from start up to, but excluding, end.",
+        RANGE_BUILTIN,
+    ),
+    Builtin::new(
+        "to_array",
+        Some(1),
+        "Materializes a range into an array, or a hash into its [key, value] pairs.",
+        TO_ARRAY_BUILTIN,
+    ),
+    Builtin::new(
+        "group_by",
+        Some(2),
+        "Groups the elements of an array into a hash of arrays by the key function's result.",
+        GROUP_BY_BUILTIN,
+    ),
+    Builtin::new(
+        "sort_by",
+        Some(2),
+        "Returns a new array stably sorted by the key function's result.",
+        SORT_BY_BUILTIN,
+    ),
+    Builtin::new(
+        "wrapping_add",
+        Some(2),
+        "Adds two integers, wrapping around on overflow.",
+        WRAPPING_ADD_BUILTIN,
+    ),
+    Builtin::new(
+        "wrapping_sub",
+        Some(2),
+        "Subtracts two integers, wrapping around on overflow.",
+        WRAPPING_SUB_BUILTIN,
+    ),
+    Builtin::new(
+        "wrapping_mul",
+        Some(2),
+        "Multiplies two integers, wrapping around on overflow.",
+        WRAPPING_MUL_BUILTIN,
+    ),
+    Builtin::new(
+        "entries",
+        Some(1),
+        "Returns the [key, value] pairs of a hash in insertion order.",
+        ENTRIES_BUILTIN,
+    ),
+    Builtin::new(
+        "from_entries",
+        Some(1),
+        "Builds a hash from an array of [key, value] pairs.",
+        FROM_ENTRIES_BUILTIN,
+    ),
+    Builtin::new(
+        "assert_eq",
+        Some(2),
+        "Returns null if both values are structurally equal, or an error describing them if not.",
+        ASSERT_EQ_BUILTIN,
+    ),
+    Builtin::new(
+        "clamp",
+        Some(3),
+        "Constrains a number to lie between a lower and an upper bound.",
+        CLAMP_BUILTIN,
+    ),
+    Builtin::new(
+        "values_of_type",
+        Some(2),
+        "Returns the elements of an array whose type matches the given type name.",
+        VALUES_OF_TYPE_BUILTIN,
+    ),
+    Builtin::new(
+        "pretty",
+        Some(1),
+        "Renders a value as a string, with nested arrays and hashes indented across lines.",
+        PRETTY_BUILTIN,
+    ),
+    Builtin::new(
+        "size_of",
+        Some(1),
+        "Returns a rough structural size: elements of a collection, characters of a string, or 1.",
+        SIZE_OF_BUILTIN,
+    ),
+    Builtin::new(
+        "env_get",
+        Some(1),
+        "Returns the value of an environment variable, or null if it is unset.",
+        ENV_GET_BUILTIN,
+    ),
+    Builtin::new(
+        "env_set",
+        Some(2),
+        "Sets an environment variable for the current process.",
+        ENV_SET_BUILTIN,
+    ),
+    Builtin::new(
+        "parse_monkey",
+        Some(1),
+        "Parses Monkey source into a quoted AST node.",
+        PARSE_MONKEY_BUILTIN,
+    ),
+    Builtin::new(
+        "eval",
+        Some(1),
+        "Evaluates a quoted AST node or a string of Monkey source.",
+        EVAL_BUILTIN,
+    ),
+    Builtin::new(
+        "globals",
+        Some(0),
+        "Returns a hash of the global bindings, with functions shown as placeholders.",
+        GLOBALS_BUILTIN,
+    ),
+    Builtin::new(
+        "locals",
+        Some(0),
+        "Returns a hash of the innermost scope's bindings, with functions shown as placeholders.",
+        LOCALS_BUILTIN,
+    ),
+    Builtin::new(
+        "is_empty",
+        Some(1),
+        "Returns whether a string, array, range or hash has no elements.",
+        IS_EMPTY_BUILTIN,
+    ),
+    Builtin::new(
+        "concat",
+        None,
+        "Joins any number of arrays into one.",
+        CONCAT_BUILTIN,
+    ),
+    Builtin::new(
+        "fill",
+        Some(2),
+        "Returns an array holding a number of copies of a value.",
+        FILL_BUILTIN,
+    ),
+    Builtin::new(
+        "swap",
+        Some(3),
+        "Returns a copy of an array with the elements at two indices exchanged.",
+        SWAP_BUILTIN,
+    ),
+    Builtin::new(
+        "insert",
+        Some(3),
+        "Returns a copy of an array with a value inserted at an index, shifting later elements.",
+        INSERT_BUILTIN,
+    ),
+    Builtin::new(
+        "remove_at",
+        Some(2),
+        "Returns a copy of an array without the element at an index.",
+        REMOVE_AT_BUILTIN,
+    ),
+    Builtin::new(
+        "any",
+        Some(2),
+        "Returns whether a predicate is truthy for at least one element of an array.",
+        ANY_BUILTIN,
+    ),
+    Builtin::new(
+        "all",
+        Some(2),
+        "Returns whether a predicate is truthy for every element of an array.",
+        ALL_BUILTIN,
+    ),
+    Builtin::new(
+        "times",
+        Some(2),
+        "Calls a function with each index from 0 up to a count, discarding the results.",
+        TIMES_BUILTIN,
+    ),
+    Builtin::new(
+        "bool",
+        Some(1),
+        "Converts any value to a boolean; only false and null are false.",
+        BOOL_BUILTIN,
+    ),
+    #[cfg(feature = "io")]
+    Builtin::new(
+        "read_file",
+        Some(1),
+        "Returns the contents of a file as a string.",
+        READ_FILE_BUILTIN,
+    ),
+    #[cfg(feature = "io")]
+    Builtin::new(
+        "write_file",
+        Some(2),
+        "Writes a string to a file, replacing any existing contents.",
+        WRITE_FILE_BUILTIN,
+    ),
 ];
 
-static LEN_BUILTIN: BuiltinFn = |objs| {
-    if objs.len() != 1 {
-        return Rc::new(new_error(format!(
-            "wrong number of arguments: expected 1, found {}",
-            objs.len()
-        )));
+fn is_callable(obj: &Object) -> bool {
+    matches!(
+        obj,
+        Object::Function { .. } | Object::BuiltinFn(_) | Object::Closure(_)
+    )
+}
+
+pub static SANDBOX_DISABLED_BUILTINS: [&str; 5] =
+    ["exit", "env_get", "env_set", "read_file", "write_file"];
+
+static LEN_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::String(string) => Rc::new(Object::Integer(string.len() as Integer)),
+    Object::Array(array) => Rc::new(Object::Integer(array.len() as Integer)),
+    Object::Range { start, end, step } => match Integer::try_from(range_len(*start, *end, *step)) {
+        Ok(len) => Rc::new(Object::Integer(len)),
+        Err(_) => Rc::new(new_error("range is too long for 'len'".to_string())),
+    },
+    _ => Rc::new(new_error(format!(
+        "argument to 'len' not supported, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static FIRST_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Array(elements) => {
+        if !elements.is_empty() {
+            elements[0].clone()
+        } else {
+            Rc::new(Object::Null)
+        }
     }
+    _ => Rc::new(new_error(format!(
+        "argument to 'first' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
 
+static LAST_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Array(elements) => elements.last().unwrap_or(&Rc::new(Object::Null)).clone(),
+    _ => Rc::new(new_error(format!(
+        "argument to 'last' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static REST_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Array(elements) => {
+        if !elements.is_empty() {
+            Rc::new(Object::Array(elements[1..].to_vec()))
+        } else {
+            Rc::new(Object::Null)
+        }
+    }
+    _ => Rc::new(new_error(format!(
+        "argument to 'rest' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static PUSH_BUILTIN: BuiltinFn = |objs| {
     match &objs[0].as_ref() {
-        Object::String(string) => Rc::new(Object::Integer(string.len() as i32)),
-        Object::Array(array) => Rc::new(Object::Integer(array.len() as i32)),
+        Object::Array(elements) => {
+            // The array can't be extended in place: builtins only borrow their arguments, and
+            // an array passed by name is still bound in the caller's environment, so it is never
+            // uniquely owned here. Reserving the final size keeps the copy to one allocation.
+            let mut pushed = Vec::with_capacity(elements.len() + 1);
+            pushed.extend_from_slice(elements);
+            pushed.push(objs[1].clone());
+            Rc::new(Object::Array(pushed))
+        }
         _ => Rc::new(new_error(format!(
-            "argument to 'len' not supported, found {}",
+            "argument to 'push' must be ARRAY, found {}",
             objs[0].get_type_str()
         ))),
     }
 };
 
-static FIRST_BUILTIN: BuiltinFn = |objs| {
-    if objs.len() != 1 {
-        return Rc::new(new_error(format!(
-            "wrong number of arguments: expected 1, found {}",
-            objs.len()
-        )));
+pub type Printer = fn(&str);
+
+thread_local! {
+    static PRINTER: Cell<Printer> = Cell::new(|line| println!("{}", line));
+}
+
+/// Replaces the function used by `puts` to write a line on the current thread.
+pub fn set_printer(printer: Printer) {
+    PRINTER.with(|cell| cell.set(printer));
+}
+
+static PUTS_BUILTIN: BuiltinFn = |objs| {
+    let line = objs
+        .iter()
+        .map(|obj| obj.to_string())
+        .collect::<Vec<String>>()
+        .join(" ");
+    PRINTER.with(|cell| cell.get()(&line));
+
+    Rc::new(Object::Null)
+};
+
+// `i32::try_from` is a no-op unless the `i64` feature is enabled.
+#[allow(clippy::useless_conversion)]
+static EXIT_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Integer(code) => match i32::try_from(*code) {
+        Ok(code) => Rc::new(Object::Exit(code)),
+        Err(_) => Rc::new(new_error(format!("exit code out of range: {}", code))),
+    },
+    _ => Rc::new(new_error(format!(
+        "argument to 'exit' must be INTEGER, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static DEEP_EQUAL_BUILTIN: BuiltinFn =
+    |objs| Rc::new(Object::Boolean(deep_equal(&objs[0], &objs[1])));
+
+static APPLY_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (function, Object::Array(args)) if is_callable(function) => {
+        apply_callback(function, args).unwrap_or_else(|err| Rc::new(err.into()))
+    }
+    (function, _) if is_callable(function) => Rc::new(new_error(format!(
+        "second argument to 'apply' must be ARRAY, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'apply' must be FUNCTION, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+// Builds `fn(x) { f(g(x)) }` with `f` and `g` bound in an environment enclosing the one the
+// composed functions close over, so a sandbox stays in force.
+static COMPOSE_BUILTIN: BuiltinFn = |objs| {
+    for obj in objs.iter() {
+        if !is_callable(obj) {
+            return Rc::new(new_error(format!(
+                "arguments to 'compose' must be FUNCTION, found {}",
+                obj.get_type_str()
+            )));
+        }
     }
 
-    match &objs[0].as_ref() {
-        Object::Array(elements) => {
-            if !elements.is_empty() {
-                elements[0].clone()
+    let closures: Vec<_> = objs
+        .iter()
+        .filter_map(|obj| match obj.as_ref() {
+            Object::Function { env, .. } => Some(env),
+            _ => None,
+        })
+        .collect();
+    let mut env = match closures
+        .iter()
+        .find(|env| env.borrow().is_sandboxed())
+        .or(closures.first())
+    {
+        Some(outer) => Environment::new_enclosed(Rc::clone(outer)),
+        None => Environment::new(),
+    };
+    for (name, obj) in ["f", "g"].iter().zip(objs.iter()) {
+        if let Err(err) = env.set(name.to_string(), obj.clone()) {
+            return Rc::new(new_error(err));
+        }
+    }
+
+    let call = |name: &str, arg: Expression| {
+        Expression::Call(CallExpression {
+            function: Box::new(Expression::Identifier(IdentifierExpression {
+                name: name.to_string(),
+            })),
+            arguments: vec![arg],
+        })
+    };
+    let x = Expression::Identifier(IdentifierExpression {
+        name: "x".to_string(),
+    });
+
+    Rc::new(Object::Function {
+        parameters: vec![IdentifierExpression {
+            name: "x".to_string(),
+        }],
+        body: BlockStatement {
+            statements: vec![Statement::Expression(ExpressionStatement {
+                expr: call("f", call("g", x)),
+            })],
+        },
+        env: Rc::new(RefCell::new(env)),
+    })
+};
+
+static REPEAT_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Integer(n), function) if is_callable(function) => {
+        let mut results = Vec::new();
+        for i in 0..*n {
+            match apply_callback(function, &[Rc::new(Object::Integer(i))]) {
+                Ok(result) if matches!(result.as_ref(), Object::Error(_)) => return result,
+                Ok(result) => results.push(result),
+                Err(err) => return Rc::new(err.into()),
+            }
+        }
+        Rc::new(Object::Array(results))
+    }
+    (Object::Integer(_), _) => Rc::new(new_error(format!(
+        "second argument to 'repeat' must be FUNCTION, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'repeat' must be INTEGER, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static ZIP_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(lhs), Object::Array(rhs)) => Rc::new(Object::Array(
+        lhs.iter()
+            .zip(rhs.iter())
+            .map(|(l, r)| Rc::new(Object::Array(vec![l.clone(), r.clone()])))
+            .collect(),
+    )),
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'zip' must be ARRAY, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'zip' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static UNIQUE_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Array(elements) => {
+        let mut unique: Vec<Rc<Object>> = Vec::new();
+        for element in elements.iter() {
+            if !unique.iter().any(|seen| deep_equal(seen, element)) {
+                unique.push(element.clone());
+            }
+        }
+        Rc::new(Object::Array(unique))
+    }
+    _ => Rc::new(new_error(format!(
+        "argument to 'unique' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+// Only the predicate form is supported; count by value with `count(arr, fn(x) { x == value })`.
+static COUNT_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), predicate) if is_callable(predicate) => {
+        count_matching(elements.iter().cloned(), predicate)
+    }
+    (Object::Range { start, end, step }, predicate) if is_callable(predicate) => count_matching(
+        range_values(*start, *end, *step).map(|value| Rc::new(Object::Integer(value))),
+        predicate,
+    ),
+    (Object::Array(_) | Object::Range { .. }, _) => Rc::new(new_error(format!(
+        "second argument to 'count' must be FUNCTION, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'count' must be ARRAY or RANGE, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+fn count_matching(elements: impl Iterator<Item = Rc<Object>>, predicate: &Object) -> Rc<Object> {
+    let mut count = 0;
+    for element in elements {
+        match apply_callback(predicate, slice::from_ref(&element)) {
+            Ok(result) if matches!(result.as_ref(), Object::Error(_)) => return result,
+            Ok(result) if is_truthy(&result) => count += 1,
+            Ok(_) => {}
+            Err(err) => return Rc::new(err.into()),
+        }
+    }
+    Rc::new(Object::Integer(count))
+}
+
+static TAKE_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), Object::Integer(n)) => {
+        let end = (*n).clamp(0, elements.len() as Integer) as usize;
+        Rc::new(Object::Array(elements[..end].to_vec()))
+    }
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'take' must be INTEGER, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'take' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static DROP_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), Object::Integer(n)) => {
+        let start = (*n).clamp(0, elements.len() as Integer) as usize;
+        Rc::new(Object::Array(elements[start..].to_vec()))
+    }
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'drop' must be INTEGER, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'drop' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static PAD_START_BUILTIN: BuiltinFn = |objs| pad_string(objs, "pad_start", true);
+
+static PAD_END_BUILTIN: BuiltinFn = |objs| pad_string(objs, "pad_end", false);
+
+fn pad_string(objs: &[Rc<Object>], name: &str, at_start: bool) -> Rc<Object> {
+    match (objs[0].as_ref(), objs[1].as_ref(), objs[2].as_ref()) {
+        (Object::String(string), Object::Integer(width), Object::String(pad)) => {
+            if pad.chars().count() != 1 {
+                return Rc::new(new_error(format!(
+                    "third argument to '{}' must be a single character, found \"{}\"",
+                    name, pad
+                )));
+            }
+
+            let width = (*width).max(0) as usize;
+            let padding = pad.repeat(width.saturating_sub(string.chars().count()));
+            if at_start {
+                Rc::new(Object::String(padding + string))
             } else {
-                Rc::new(Object::Null)
+                Rc::new(Object::String(string.to_owned() + &padding))
             }
         }
+        (Object::String(_), Object::Integer(_), _) => Rc::new(new_error(format!(
+            "third argument to '{}' must be STRING, found {}",
+            name,
+            objs[2].get_type_str()
+        ))),
+        (Object::String(_), _, _) => Rc::new(new_error(format!(
+            "second argument to '{}' must be INTEGER, found {}",
+            name,
+            objs[1].get_type_str()
+        ))),
         _ => Rc::new(new_error(format!(
-            "argument to 'first' must be ARRAY, found {}",
+            "first argument to '{}' must be STRING, found {}",
+            name,
             objs[0].get_type_str()
         ))),
     }
+}
+
+static HASH_GET_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Hash(pairs) => match objs[1].get_hash_key() {
+        Some(hash_key) => match pairs.get(&hash_key) {
+            Some(pair) => pair.value.clone(),
+            None => objs[2].clone(),
+        },
+        None => Rc::new(new_error(format!(
+            "unusable as hash key: {}",
+            objs[1].get_type_str()
+        ))),
+    },
+    _ => Rc::new(new_error(format!(
+        "first argument to 'hash_get' must be HASH, found {}",
+        objs[0].get_type_str()
+    ))),
 };
 
-static LAST_BUILTIN: BuiltinFn = |objs| {
-    if objs.len() != 1 {
-        return Rc::new(new_error(format!(
-            "wrong number of arguments: expected 1, found {}",
-            objs.len()
-        )));
-    }
+static PUT_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Hash(pairs) => match objs[1].get_hash_key() {
+        Some(hash_key) => {
+            let mut pairs = pairs.clone();
+            pairs.insert(
+                hash_key,
+                HashPair {
+                    key: objs[1].clone(),
+                    value: objs[2].clone(),
+                },
+            );
+            Rc::new(Object::Hash(pairs))
+        }
+        None => Rc::new(new_error(format!(
+            "unusable as hash key: {}",
+            objs[1].get_type_str()
+        ))),
+    },
+    _ => Rc::new(new_error(format!(
+        "first argument to 'put' must be HASH, found {}",
+        objs[0].get_type_str()
+    ))),
+};
 
-    match &objs[0].as_ref() {
-        Object::Array(elements) => elements.last().unwrap_or(&Rc::new(Object::Null)).clone(),
+static MIN_BY_BUILTIN: BuiltinFn = |objs| select_by(objs, "min_by", Ordering::Less);
+
+static MAX_BY_BUILTIN: BuiltinFn = |objs| select_by(objs, "max_by", Ordering::Greater);
+
+fn select_by(objs: &[Rc<Object>], name: &str, wanted: Ordering) -> Rc<Object> {
+    match (objs[0].as_ref(), objs[1].as_ref()) {
+        (Object::Array(elements), key_fn) if is_callable(key_fn) => {
+            let mut selected: Option<(Rc<Object>, Rc<Object>)> = None;
+            for element in elements.iter() {
+                let key = match apply_callback(key_fn, slice::from_ref(element)) {
+                    Ok(key) if matches!(key.as_ref(), Object::Error(_)) => return key,
+                    Ok(key) => key,
+                    Err(err) => return Rc::new(err.into()),
+                };
+                let ordering = match (&selected, key.as_ref()) {
+                    (None, Object::Integer(_) | Object::String(_)) => wanted,
+                    (Some((_, best)), _) => match (best.as_ref(), key.as_ref()) {
+                        (Object::Integer(best), Object::Integer(key)) => key.cmp(best),
+                        (Object::String(best), Object::String(key)) => key.cmp(best),
+                        _ => {
+                            return Rc::new(new_error(format!(
+                                "cannot compare keys in '{}': {} and {}",
+                                name,
+                                best.get_type_str(),
+                                key.get_type_str()
+                            )))
+                        }
+                    },
+                    (None, _) => {
+                        return Rc::new(new_error(format!(
+                            "keys in '{}' must be INTEGER or STRING, found {}",
+                            name,
+                            key.get_type_str()
+                        )))
+                    }
+                };
+                if ordering == wanted {
+                    selected = Some((element.clone(), key));
+                }
+            }
+
+            match selected {
+                Some((element, _)) => element,
+                None => Rc::new(new_error(format!(
+                    "argument to '{}' must not be an empty ARRAY",
+                    name
+                ))),
+            }
+        }
+        (Object::Array(_), _) => Rc::new(new_error(format!(
+            "second argument to '{}' must be FUNCTION, found {}",
+            name,
+            objs[1].get_type_str()
+        ))),
         _ => Rc::new(new_error(format!(
-            "argument to 'last' must be ARRAY, found {}",
+            "first argument to '{}' must be ARRAY, found {}",
+            name,
             objs[0].get_type_str()
         ))),
     }
+}
+
+static RAISE_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::String(message) => Rc::new(new_error(message.to_owned())),
+    _ => Rc::new(new_error(format!(
+        "argument to 'raise' must be STRING, found {}",
+        objs[0].get_type_str()
+    ))),
 };
 
-static REST_BUILTIN: BuiltinFn = |objs| {
-    if objs.len() != 1 {
-        return Rc::new(new_error(format!(
-            "wrong number of arguments: expected 1, found {}",
-            objs.len()
-        )));
-    }
+static HELP_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::String(name) => match get_builtin_fn(name) {
+        Some(builtin) => Rc::new(Object::String(builtin.description.to_string())),
+        None => Rc::new(new_error(format!("unknown builtin: {}", name))),
+    },
+    _ => Rc::new(new_error(format!(
+        "argument to 'help' must be STRING, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static FLOAT_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Integer(integer) => Rc::new(Object::Float(*integer as f64)),
+    Object::Float(_) => objs[0].clone(),
+    _ => Rc::new(new_error(format!(
+        "argument to 'float' must be INTEGER or FLOAT, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static FLOOR_BUILTIN: BuiltinFn = |objs| float_to_integer(objs, "floor", f64::floor);
+
+static CEIL_BUILTIN: BuiltinFn = |objs| float_to_integer(objs, "ceil", f64::ceil);
+
+static ROUND_BUILTIN: BuiltinFn = |objs| float_to_integer(objs, "round", f64::round);
 
+static TRUNCATE_BUILTIN: BuiltinFn = |objs| float_to_integer(objs, "truncate", f64::trunc);
+
+fn float_to_integer(objs: &[Rc<Object>], name: &str, convert: fn(f64) -> f64) -> Rc<Object> {
     match &objs[0].as_ref() {
-        Object::Array(elements) => {
-            if !elements.is_empty() {
-                Rc::new(Object::Array(elements[1..].to_vec()))
+        Object::Integer(_) => objs[0].clone(),
+        Object::Float(float) => {
+            let converted = convert(*float);
+            if converted.is_finite()
+                && converted >= Integer::MIN as f64
+                && converted <= Integer::MAX as f64
+            {
+                Rc::new(Object::Integer(converted as Integer))
             } else {
-                Rc::new(Object::Null)
+                Rc::new(new_error(format!(
+                    "argument to '{}' out of INTEGER range: {:?}",
+                    name, float
+                )))
             }
         }
         _ => Rc::new(new_error(format!(
-            "argument to 'rest' must be ARRAY, found {}",
+            "argument to '{}' must be FLOAT or INTEGER, found {}",
+            name,
             objs[0].get_type_str()
         ))),
     }
+}
+
+static SQRT_BUILTIN: BuiltinFn = |objs| match to_float(&objs[0]) {
+    Some(x) if x < 0.0 => Rc::new(new_error(format!(
+        "argument to 'sqrt' must not be negative, found {:?}",
+        x
+    ))),
+    Some(x) => Rc::new(Object::Float(x.sqrt())),
+    None => float_argument_error("sqrt", &objs[0]),
 };
 
-static PUSH_BUILTIN: BuiltinFn = |objs| {
-    if objs.len() != 2 {
-        return Rc::new(new_error(format!(
-            "wrong number of arguments: expected 2, found {}",
-            objs.len()
-        )));
+static POW_BUILTIN: BuiltinFn = |objs| match (to_float(&objs[0]), to_float(&objs[1])) {
+    (Some(base), Some(exponent)) => Rc::new(Object::Float(base.powf(exponent))),
+    (None, _) => float_argument_error("pow", &objs[0]),
+    (_, None) => float_argument_error("pow", &objs[1]),
+};
+
+static LOG_BUILTIN: BuiltinFn = |objs| match to_float(&objs[0]) {
+    Some(x) if x <= 0.0 => Rc::new(new_error(format!(
+        "argument to 'log' must be positive, found {:?}",
+        x
+    ))),
+    Some(x) => Rc::new(Object::Float(x.ln())),
+    None => float_argument_error("log", &objs[0]),
+};
+
+static SIN_BUILTIN: BuiltinFn = |objs| match to_float(&objs[0]) {
+    Some(x) => Rc::new(Object::Float(x.sin())),
+    None => float_argument_error("sin", &objs[0]),
+};
+
+static COS_BUILTIN: BuiltinFn = |objs| match to_float(&objs[0]) {
+    Some(x) => Rc::new(Object::Float(x.cos())),
+    None => float_argument_error("cos", &objs[0]),
+};
+
+fn to_float(obj: &Object) -> Option<f64> {
+    match obj {
+        Object::Integer(integer) => Some(*integer as f64),
+        Object::Float(float) => Some(*float),
+        _ => None,
     }
+}
 
-    match &objs[0].as_ref() {
-        Object::Array(elements) => {
-            let mut elements = elements.to_vec();
-            elements.push(objs[1].clone());
-            Rc::new(Object::Array(elements))
+fn float_argument_error(name: &str, obj: &Object) -> Rc<Object> {
+    Rc::new(new_error(format!(
+        "argument to '{}' must be FLOAT or INTEGER, found {}",
+        name,
+        obj.get_type_str()
+    )))
+}
+
+pub type Clock = fn() -> f64;
+
+thread_local! {
+    static CLOCK: Cell<Clock> = Cell::new(system_clock);
+}
+
+/// Replaces the clock used by `time_now` on the current thread.
+pub fn set_clock(clock: Clock) {
+    CLOCK.with(|cell| cell.set(clock));
+}
+
+fn system_clock() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as f64)
+        .unwrap_or(0.0)
+}
+
+// Milliseconds since the epoch overflow INTEGER, so the time is returned as a FLOAT.
+static TIME_NOW_BUILTIN: BuiltinFn = |_| Rc::new(Object::Float(CLOCK.with(|cell| cell.get()())));
+
+pub type Sleeper = fn(Duration);
+
+thread_local! {
+    static SLEEPER: Cell<Sleeper> = Cell::new(thread::sleep);
+}
+
+/// Replaces the function used by `sleep` on the current thread.
+pub fn set_sleeper(sleeper: Sleeper) {
+    SLEEPER.with(|cell| cell.set(sleeper));
+}
+
+static SLEEP_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Integer(ms) if *ms >= 0 => {
+        SLEEPER.with(|cell| cell.get()(Duration::from_millis(*ms as u64)));
+        Rc::new(Object::Null)
+    }
+    Object::Integer(ms) => Rc::new(new_error(format!(
+        "argument to 'sleep' must not be negative, found {}",
+        ms
+    ))),
+    _ => Rc::new(new_error(format!(
+        "argument to 'sleep' must be INTEGER, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static RANGE_BUILTIN: BuiltinFn = |objs| {
+    let mut bounds = Vec::with_capacity(objs.len());
+    for obj in objs.iter() {
+        match obj.as_ref() {
+            Object::Integer(integer) => bounds.push(*integer),
+            _ => {
+                return Rc::new(new_error(format!(
+                    "arguments to 'range' must be INTEGER, found {}",
+                    obj.get_type_str()
+                )))
+            }
+        }
+    }
+
+    let (start, end, step) = match bounds[..] {
+        [end] => (0, end, 1),
+        [start, end] => (start, end, 1),
+        [start, end, step] => (start, end, step),
+        _ => {
+            return Rc::new(new_error(format!(
+                "wrong number of arguments: expected 1 to 3, found {}",
+                objs.len()
+            )))
+        }
+    };
+    if step == 0 {
+        return Rc::new(new_error("step of 'range' must not be zero".to_string()));
+    }
+    Rc::new(Object::Range { start, end, step })
+};
+
+static TO_ARRAY_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Array(_) => objs[0].clone(),
+    Object::Range { start, end, step } => Rc::new(Object::Array(
+        range_values(*start, *end, *step)
+            .map(|value| Rc::new(Object::Integer(value)))
+            .collect(),
+    )),
+    Object::Hash(pairs) => hash_entries(pairs),
+    _ => Rc::new(new_error(format!(
+        "argument to 'to_array' must be ARRAY, RANGE or HASH, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static GROUP_BY_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), key_fn) if is_callable(key_fn) => {
+        let mut groups: Vec<(HashKey, Rc<Object>, Vec<Rc<Object>>)> = Vec::new();
+        let mut indices: BTreeMap<HashKey, usize> = BTreeMap::new();
+        for element in elements.iter() {
+            let key = match apply_callback(key_fn, slice::from_ref(element)) {
+                Ok(key) if matches!(key.as_ref(), Object::Error(_)) => return key,
+                Ok(key) => key,
+                Err(err) => return Rc::new(err.into()),
+            };
+            let Some(hash_key) = key.get_hash_key() else {
+                return Rc::new(new_error(format!(
+                    "unusable as hash key: {}",
+                    key.get_type_str()
+                )));
+            };
+            match indices.get(&hash_key) {
+                Some(&index) => groups[index].2.push(element.clone()),
+                None => {
+                    indices.insert(hash_key.clone(), groups.len());
+                    groups.push((hash_key, key, vec![element.clone()]));
+                }
+            }
+        }
+
+        let mut pairs = HashPairs::new();
+        for (hash_key, key, group) in groups {
+            pairs.insert(
+                hash_key,
+                HashPair {
+                    key,
+                    value: Rc::new(Object::Array(group)),
+                },
+            );
+        }
+        Rc::new(Object::Hash(pairs))
+    }
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'group_by' must be FUNCTION, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'group_by' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static SORT_BY_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), key_fn) if is_callable(key_fn) => {
+        let mut keyed = Vec::with_capacity(elements.len());
+        for element in elements.iter() {
+            match apply_callback(key_fn, slice::from_ref(element)) {
+                Ok(key) if matches!(key.as_ref(), Object::Error(_)) => return key,
+                Ok(key) => match key.as_ref() {
+                    Object::Integer(_) | Object::String(_) => keyed.push((key, element.clone())),
+                    _ => {
+                        return Rc::new(new_error(format!(
+                            "keys in 'sort_by' must be INTEGER or STRING, found {}",
+                            key.get_type_str()
+                        )))
+                    }
+                },
+                Err(err) => return Rc::new(err.into()),
+            }
+        }
+
+        if let Some(pair) = keyed
+            .windows(2)
+            .find(|pair| pair[0].0.get_type_str() != pair[1].0.get_type_str())
+        {
+            return Rc::new(new_error(format!(
+                "cannot compare keys in 'sort_by': {} and {}",
+                pair[0].0.get_type_str(),
+                pair[1].0.get_type_str()
+            )));
+        }
+
+        keyed.sort_by(|(lhs, _), (rhs, _)| match (lhs.as_ref(), rhs.as_ref()) {
+            (Object::Integer(lhs), Object::Integer(rhs)) => lhs.cmp(rhs),
+            (Object::String(lhs), Object::String(rhs)) => lhs.cmp(rhs),
+            _ => Ordering::Equal,
+        });
+        Rc::new(Object::Array(
+            keyed.into_iter().map(|(_, element)| element).collect(),
+        ))
+    }
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'sort_by' must be FUNCTION, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'sort_by' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static WRAPPING_ADD_BUILTIN: BuiltinFn =
+    |objs| wrapping_operation("wrapping_add", objs, Integer::wrapping_add);
+
+static WRAPPING_SUB_BUILTIN: BuiltinFn =
+    |objs| wrapping_operation("wrapping_sub", objs, Integer::wrapping_sub);
+
+static WRAPPING_MUL_BUILTIN: BuiltinFn =
+    |objs| wrapping_operation("wrapping_mul", objs, Integer::wrapping_mul);
+
+fn wrapping_operation(
+    name: &str,
+    objs: &[Rc<Object>],
+    operation: fn(Integer, Integer) -> Integer,
+) -> Rc<Object> {
+    match (objs[0].as_ref(), objs[1].as_ref()) {
+        (Object::Integer(lhs), Object::Integer(rhs)) => {
+            Rc::new(Object::Integer(operation(*lhs, *rhs)))
+        }
+        (Object::Integer(_), obj) | (obj, _) => Rc::new(new_error(format!(
+            "arguments to '{}' must be INTEGER, found {}",
+            name,
+            obj.get_type_str()
+        ))),
+    }
+}
+
+static ENTRIES_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Hash(pairs) => hash_entries(pairs),
+    _ => Rc::new(new_error(format!(
+        "argument to 'entries' must be HASH, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+fn hash_entries(pairs: &HashPairs) -> Rc<Object> {
+    Rc::new(Object::Array(
+        pairs
+            .values()
+            .map(|pair| Rc::new(Object::Array(vec![pair.key.clone(), pair.value.clone()])))
+            .collect(),
+    ))
+}
+
+static FROM_ENTRIES_BUILTIN: BuiltinFn = |objs| match &objs[0].as_ref() {
+    Object::Array(entries) => {
+        let mut pairs = HashPairs::new();
+        for entry in entries.iter() {
+            let (key, value) = match entry.as_ref() {
+                Object::Array(pair) if pair.len() == 2 => (&pair[0], &pair[1]),
+                Object::Array(pair) => {
+                    return Rc::new(new_error(format!(
+                        "entries of 'from_entries' must be [key, value] pairs, found {} elements",
+                        pair.len()
+                    )))
+                }
+                _ => {
+                    return Rc::new(new_error(format!(
+                        "entries of 'from_entries' must be ARRAY, found {}",
+                        entry.get_type_str()
+                    )))
+                }
+            };
+            match key.get_hash_key() {
+                Some(hash_key) => pairs.insert(
+                    hash_key,
+                    HashPair {
+                        key: key.clone(),
+                        value: value.clone(),
+                    },
+                ),
+                None => {
+                    return Rc::new(new_error(format!(
+                        "unusable as hash key: {}",
+                        key.get_type_str()
+                    )))
+                }
+            }
+        }
+        Rc::new(Object::Hash(pairs))
+    }
+    _ => Rc::new(new_error(format!(
+        "argument to 'from_entries' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static ASSERT_EQ_BUILTIN: BuiltinFn = |objs| {
+    let (actual, expected) = (&objs[0], &objs[1]);
+    if deep_equal(actual, expected) {
+        Rc::new(Object::Null)
+    } else if actual.to_string() == expected.to_string() {
+        // Values such as `1` and `"1"` display the same, so name their types to tell them apart.
+        Rc::new(new_error(format!(
+            "assertion failed: expected {} ({}), found {} ({})",
+            expected,
+            expected.get_type_str(),
+            actual,
+            actual.get_type_str()
+        )))
+    } else {
+        Rc::new(new_error(format!(
+            "assertion failed: expected {}, found {}",
+            expected, actual
+        )))
+    }
+};
+
+static CLAMP_BUILTIN: BuiltinFn =
+    |objs| match (objs[0].as_ref(), objs[1].as_ref(), objs[2].as_ref()) {
+        (Object::Integer(_), Object::Integer(lo), Object::Integer(hi)) if lo > hi => {
+            clamp_bounds_error(&objs[1], &objs[2])
+        }
+        (Object::Integer(x), Object::Integer(lo), Object::Integer(hi)) => {
+            Rc::new(Object::Integer(*x.clamp(lo, hi)))
+        }
+        (Object::Float(_), Object::Float(lo), Object::Float(hi))
+            if lo > hi || lo.is_nan() || hi.is_nan() =>
+        {
+            clamp_bounds_error(&objs[1], &objs[2])
+        }
+        (Object::Float(x), Object::Float(lo), Object::Float(hi)) => {
+            Rc::new(Object::Float(x.clamp(*lo, *hi)))
         }
         _ => Rc::new(new_error(format!(
-            "argument to 'push' must be ARRAY, found {}",
-            objs[0].get_type_str()
+            "arguments to 'clamp' must be all INTEGER or all FLOAT, found {}, {}, {}",
+            objs[0].get_type_str(),
+            objs[1].get_type_str(),
+            objs[2].get_type_str()
         ))),
+    };
+
+fn clamp_bounds_error(lo: &Object, hi: &Object) -> Rc<Object> {
+    Rc::new(new_error(format!(
+        "bounds of 'clamp' must satisfy lo <= hi, found {} and {}",
+        lo, hi
+    )))
+}
+
+static VALUES_OF_TYPE_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), Object::String(type_str)) => Rc::new(Object::Array(
+        elements
+            .iter()
+            .filter(|element| element.get_type_str() == *type_str)
+            .cloned()
+            .collect(),
+    )),
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'values_of_type' must be STRING, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'values_of_type' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static PRETTY_BUILTIN: BuiltinFn = |objs| Rc::new(Object::String(objs[0].pretty()));
+
+static SIZE_OF_BUILTIN: BuiltinFn = |objs| {
+    let size = match objs[0].as_ref() {
+        Object::String(string) => string.chars().count(),
+        Object::Array(elements) => elements.len(),
+        Object::Range { start, end, step } => range_len(*start, *end, *step),
+        Object::Hash(pairs) => pairs.len(),
+        _ => 1,
+    };
+    match Integer::try_from(size) {
+        Ok(size) => Rc::new(Object::Integer(size)),
+        Err(_) => Rc::new(new_error("range is too long for 'size_of'".to_string())),
     }
 };
 
-static PUTS_BUILTIN: BuiltinFn = |objs| {
+pub type EnvReader = fn(&str) -> Option<String>;
+pub type EnvWriter = fn(&str, &str);
+
+thread_local! {
+    static ENV_READER: Cell<EnvReader> = Cell::new(|name| env::var(name).ok());
+    static ENV_WRITER: Cell<EnvWriter> = Cell::new(|name, value| env::set_var(name, value));
+}
+
+/// Replaces the functions used by `env_get` and `env_set` on the current thread.
+pub fn set_env_access(reader: EnvReader, writer: EnvWriter) {
+    ENV_READER.with(|cell| cell.set(reader));
+    ENV_WRITER.with(|cell| cell.set(writer));
+}
+
+static ENV_GET_BUILTIN: BuiltinFn = |objs| match objs[0].as_ref() {
+    Object::String(name) => match ENV_READER.with(|cell| cell.get()(name)) {
+        Some(value) => Rc::new(Object::String(value)),
+        None => Rc::new(Object::Null),
+    },
+    _ => Rc::new(new_error(format!(
+        "argument to 'env_get' must be STRING, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static ENV_SET_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    // `env::set_var` panics on these rather than returning an error.
+    (Object::String(name), Object::String(value))
+        if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') =>
+    {
+        Rc::new(new_error(format!(
+            "invalid environment variable: {}={}",
+            name, value
+        )))
+    }
+    (Object::String(name), Object::String(value)) => {
+        ENV_WRITER.with(|cell| cell.get()(name, value));
+        Rc::new(Object::Null)
+    }
+    _ => Rc::new(new_error(format!(
+        "arguments to 'env_set' must be STRING, found {} and {}",
+        objs[0].get_type_str(),
+        objs[1].get_type_str()
+    ))),
+};
+
+// A lone expression is quoted as itself rather than as a one-statement program.
+static PARSE_MONKEY_BUILTIN: BuiltinFn = |objs| match objs[0].as_ref() {
+    Object::String(source) => match parse_program(source) {
+        Ok(program) => {
+            let node = match program.0.as_slice() {
+                [Statement::Expression(stmt)] => Node::Expression(stmt.expr.clone()),
+                _ => Node::Program(program),
+            };
+            Rc::new(Object::Quote(Rc::new(node)))
+        }
+        Err(errs) => Rc::new(new_error(format!("parse error: {}", errs.join("; ")))),
+    },
+    _ => Rc::new(new_error(format!(
+        "argument to 'parse_monkey' must be STRING, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+// Direct calls are handled by the evaluator, which runs them in the caller's environment. When
+// called any other way (passed as a value, or from the VM) there is no environment to use, and
+// running in a fresh one would escape a sandboxed caller.
+static EVAL_BUILTIN: BuiltinFn =
+    |_| Rc::new(new_error("'eval' must be called directly".to_string()));
+
+// Like `eval`, these need the caller's environment.
+static GLOBALS_BUILTIN: BuiltinFn =
+    |_| Rc::new(new_error("'globals' must be called directly".to_string()));
+
+static LOCALS_BUILTIN: BuiltinFn =
+    |_| Rc::new(new_error("'locals' must be called directly".to_string()));
+
+static IS_EMPTY_BUILTIN: BuiltinFn = |objs| {
+    let is_empty = match objs[0].as_ref() {
+        Object::String(string) => string.is_empty(),
+        Object::Array(elements) => elements.is_empty(),
+        Object::Range { start, end, step } => range_len(*start, *end, *step) == 0,
+        Object::Hash(pairs) => pairs.is_empty(),
+        _ => {
+            return Rc::new(new_error(format!(
+                "argument to 'is_empty' must be STRING, ARRAY, RANGE or HASH, found {}",
+                objs[0].get_type_str()
+            )))
+        }
+    };
+    Rc::new(Object::Boolean(is_empty))
+};
+
+static CONCAT_BUILTIN: BuiltinFn = |objs| {
+    let mut joined = Vec::new();
     for obj in objs.iter() {
-        println!("{}", obj);
+        match obj.as_ref() {
+            Object::Array(elements) => joined.extend_from_slice(elements),
+            _ => {
+                return Rc::new(new_error(format!(
+                    "arguments to 'concat' must be ARRAY, found {}",
+                    obj.get_type_str()
+                )))
+            }
+        }
     }
+    Rc::new(Object::Array(joined))
+};
 
-    Rc::new(Object::Null)
+static FILL_BUILTIN: BuiltinFn = |objs| match objs[1].as_ref() {
+    Object::Integer(count) if *count >= 0 => Rc::new(Object::Array(
+        (0..*count)
+            .map(|_| Rc::new(objs[0].as_ref().clone()))
+            .collect(),
+    )),
+    Object::Integer(count) => Rc::new(new_error(format!(
+        "second argument to 'fill' must not be negative, found {}",
+        count
+    ))),
+    _ => Rc::new(new_error(format!(
+        "second argument to 'fill' must be INTEGER, found {}",
+        objs[1].get_type_str()
+    ))),
+};
+
+static SWAP_BUILTIN: BuiltinFn = |objs| {
+    let elements = match objs[0].as_ref() {
+        Object::Array(elements) => elements,
+        _ => {
+            return Rc::new(new_error(format!(
+                "first argument to 'swap' must be ARRAY, found {}",
+                objs[0].get_type_str()
+            )))
+        }
+    };
+
+    let mut indices = [0; 2];
+    for (index, obj) in indices.iter_mut().zip(objs[1..].iter()) {
+        *index = match obj.as_ref() {
+            Object::Integer(i) if *i >= 0 && (*i as usize) < elements.len() => *i as usize,
+            Object::Integer(i) => {
+                return Rc::new(new_error(format!(
+                    "index out of range for 'swap': {} (length {})",
+                    i,
+                    elements.len()
+                )))
+            }
+            _ => {
+                return Rc::new(new_error(format!(
+                    "indices to 'swap' must be INTEGER, found {}",
+                    obj.get_type_str()
+                )))
+            }
+        };
+    }
+
+    let mut swapped = elements.clone();
+    swapped.swap(indices[0], indices[1]);
+    Rc::new(Object::Array(swapped))
+};
+
+// Inserting at the length appends.
+static INSERT_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), Object::Integer(index))
+        if *index >= 0 && (*index as usize) <= elements.len() =>
+    {
+        let mut inserted = Vec::with_capacity(elements.len() + 1);
+        inserted.extend_from_slice(elements);
+        inserted.insert(*index as usize, objs[2].clone());
+        Rc::new(Object::Array(inserted))
+    }
+    (Object::Array(elements), Object::Integer(index)) => Rc::new(new_error(format!(
+        "index out of range for 'insert': {} (length {})",
+        index,
+        elements.len()
+    ))),
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'insert' must be INTEGER, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'insert' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static REMOVE_AT_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Array(elements), Object::Integer(index))
+        if *index >= 0 && (*index as usize) < elements.len() =>
+    {
+        let mut removed = elements.clone();
+        removed.remove(*index as usize);
+        Rc::new(Object::Array(removed))
+    }
+    (Object::Array(elements), Object::Integer(index)) => Rc::new(new_error(format!(
+        "index out of range for 'remove_at': {} (length {})",
+        index,
+        elements.len()
+    ))),
+    (Object::Array(_), _) => Rc::new(new_error(format!(
+        "second argument to 'remove_at' must be INTEGER, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'remove_at' must be ARRAY, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static ANY_BUILTIN: BuiltinFn = |objs| short_circuit_predicate("any", objs, true);
+
+static ALL_BUILTIN: BuiltinFn = |objs| short_circuit_predicate("all", objs, false);
+
+// Stops at the first element whose truthiness is `stop_on` and returns it; if there is none,
+// returns the opposite. This is `any` for true and `all` for false.
+fn short_circuit_predicate(name: &str, objs: &[Rc<Object>], stop_on: bool) -> Rc<Object> {
+    match (objs[0].as_ref(), objs[1].as_ref()) {
+        (Object::Array(elements), predicate) if is_callable(predicate) => {
+            for element in elements.iter() {
+                match apply_callback(predicate, slice::from_ref(element)) {
+                    Ok(result) if matches!(result.as_ref(), Object::Error(_)) => return result,
+                    Ok(result) if is_truthy(&result) == stop_on => {
+                        return Rc::new(Object::Boolean(stop_on))
+                    }
+                    Ok(_) => {}
+                    Err(err) => return Rc::new(err.into()),
+                }
+            }
+            Rc::new(Object::Boolean(!stop_on))
+        }
+        (Object::Array(_), _) => Rc::new(new_error(format!(
+            "second argument to '{}' must be FUNCTION, found {}",
+            name,
+            objs[1].get_type_str()
+        ))),
+        _ => Rc::new(new_error(format!(
+            "first argument to '{}' must be ARRAY, found {}",
+            name,
+            objs[0].get_type_str()
+        ))),
+    }
+}
+
+// Like `repeat`, but for side effects only.
+static TIMES_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::Integer(n), function) if is_callable(function) => {
+        for i in 0..*n {
+            match apply_callback(function, &[Rc::new(Object::Integer(i))]) {
+                Ok(result) if matches!(result.as_ref(), Object::Error(_)) => return result,
+                Ok(_) => {}
+                Err(err) => return Rc::new(err.into()),
+            }
+        }
+        Rc::new(Object::Null)
+    }
+    (Object::Integer(_), _) => Rc::new(new_error(format!(
+        "second argument to 'times' must be FUNCTION, found {}",
+        objs[1].get_type_str()
+    ))),
+    _ => Rc::new(new_error(format!(
+        "first argument to 'times' must be INTEGER, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+static BOOL_BUILTIN: BuiltinFn = |objs| Rc::new(Object::Boolean(is_truthy(&objs[0])));
+
+/// Filesystem used by `read_file` and `write_file`.
+#[cfg(feature = "io")]
+pub trait FileSystem {
+    fn read(&self, path: &str) -> std::io::Result<String>;
+    fn write(&self, path: &str, contents: &str) -> std::io::Result<()>;
+}
+
+#[cfg(feature = "io")]
+struct OsFileSystem;
+
+#[cfg(feature = "io")]
+impl FileSystem for OsFileSystem {
+    fn read(&self, path: &str) -> std::io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &str, contents: &str) -> std::io::Result<()> {
+        std::fs::write(path, contents)
+    }
+}
+
+#[cfg(feature = "io")]
+thread_local! {
+    static FILE_SYSTEM: RefCell<Rc<dyn FileSystem>> = RefCell::new(Rc::new(OsFileSystem));
+}
+
+/// Replaces the filesystem used by `read_file` and `write_file` on the current thread.
+#[cfg(feature = "io")]
+pub fn set_file_system(file_system: Rc<dyn FileSystem>) {
+    FILE_SYSTEM.with(|cell| *cell.borrow_mut() = file_system);
+}
+
+#[cfg(feature = "io")]
+static READ_FILE_BUILTIN: BuiltinFn = |objs| match objs[0].as_ref() {
+    Object::String(path) => match FILE_SYSTEM.with(|cell| cell.borrow().read(path)) {
+        Ok(contents) => Rc::new(Object::String(contents)),
+        Err(err) => Rc::new(new_error(format!("could not read file {}: {}", path, err))),
+    },
+    _ => Rc::new(new_error(format!(
+        "argument to 'read_file' must be STRING, found {}",
+        objs[0].get_type_str()
+    ))),
+};
+
+#[cfg(feature = "io")]
+static WRITE_FILE_BUILTIN: BuiltinFn = |objs| match (objs[0].as_ref(), objs[1].as_ref()) {
+    (Object::String(path), Object::String(contents)) => {
+        match FILE_SYSTEM.with(|cell| cell.borrow().write(path, contents)) {
+            Ok(()) => Rc::new(Object::Null),
+            Err(err) => Rc::new(new_error(format!("could not write file {}: {}", path, err))),
+        }
+    }
+    _ => Rc::new(new_error(format!(
+        "arguments to 'write_file' must be STRING, found {} and {}",
+        objs[0].get_type_str(),
+        objs[1].get_type_str()
+    ))),
 };
@@ -1,24 +1,46 @@
-use std::{
-    cell::RefCell,
-    collections::{hash_map::DefaultHasher, BTreeMap},
-    fmt::Display,
-    hash::{Hash, Hasher},
-    rc::Rc,
-};
-
-use crate::parser::ast::{fmt_identifier_expressions, BlockStatement, IdentifierExpression};
+use std::{cell::RefCell, collections::BTreeMap, fmt::Display, rc::Rc};
+
+use crate::parser::ast::{fmt_identifier_expressions, BlockStatement, IdentifierExpression, Node};
 use crate::{code::Instructions, evaluator::environment::Environment};
 
-use self::builtins::BuiltinFn;
+use self::builtins::Builtin;
 
 pub mod builtins;
+mod test_builtins;
+mod test_object;
+
+#[cfg(not(feature = "i64"))]
+pub type Integer = i32;
+#[cfg(feature = "i64")]
+pub type Integer = i64;
+
+// Type names as reported by `get_type_str`; error messages and scripts rely on them.
+pub const INTEGER_OBJ: &str = "INTEGER";
+pub const FLOAT_OBJ: &str = "FLOAT";
+pub const BOOLEAN_OBJ: &str = "BOOLEAN";
+pub const STRING_OBJ: &str = "STRING";
+pub const RETURN_VALUE_OBJ: &str = "RETURN_VALUE";
+pub const EXIT_OBJ: &str = "EXIT";
+pub const FUNCTION_OBJ: &str = "FUNCTION";
+pub const COMPILED_FUNCTION_OBJ: &str = "COMPILED_FUNCTION";
+pub const CLOSURE_OBJ: &str = "CLOSURE";
+pub const BUILTIN_OBJ: &str = "BUILTIN";
+pub const ARRAY_OBJ: &str = "ARRAY";
+pub const RANGE_OBJ: &str = "RANGE";
+pub const HASH_OBJ: &str = "HASH";
+pub const QUOTE_OBJ: &str = "QUOTE";
+pub const ERROR_OBJ: &str = "ERROR";
+pub const NULL_OBJ: &str = "NULL";
 
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum Object {
-    Integer(i32),
+    Integer(Integer),
+    Float(f64),
     Boolean(bool),
     String(String),
     ReturnValue(Rc<Object>),
+    Exit(i32),
     Function {
         parameters: Vec<IdentifierExpression>,
         body: BlockStatement,
@@ -26,9 +48,15 @@ pub enum Object {
     },
     CompiledFn(CompiledFn),
     Closure(Closure),
-    BuiltinFn(BuiltinFn),
+    BuiltinFn(&'static Builtin),
     Array(Vec<Rc<Object>>),
-    Hash(BTreeMap<HashKey, HashPair>),
+    Range {
+        start: Integer,
+        end: Integer,
+        step: Integer,
+    },
+    Hash(HashPairs),
+    Quote(Rc<Node>),
     Error(String),
     Null,
 }
@@ -36,42 +64,31 @@ pub enum Object {
 impl Object {
     pub fn get_type_str(&self) -> String {
         match self {
-            Object::Integer(_) => "INTEGER".to_string(),
-            Object::Boolean(_) => "BOOLEAN".to_string(),
-            Object::String(_) => "STRING".to_string(),
-            Object::ReturnValue(_) => "RETURN".to_string(),
-            Object::Function { .. } => "FUNCTION".to_string(),
-            Object::CompiledFn { .. } => "COMPILED_FUNCTION".to_string(),
-            Object::Closure(..) => "CLOSURE".to_string(),
-            Object::BuiltinFn(_) => "BUILTIN".to_string(),
-            Object::Array(_) => "ARRAY".to_string(),
-            Object::Hash(_) => "HASH".to_string(),
-            Object::Error(_) => "ERROR".to_string(),
-            Object::Null => "NULL".to_string(),
+            Object::Integer(_) => INTEGER_OBJ,
+            Object::Float(_) => FLOAT_OBJ,
+            Object::Boolean(_) => BOOLEAN_OBJ,
+            Object::String(_) => STRING_OBJ,
+            Object::ReturnValue(_) => RETURN_VALUE_OBJ,
+            Object::Exit(_) => EXIT_OBJ,
+            Object::Function { .. } => FUNCTION_OBJ,
+            Object::CompiledFn { .. } => COMPILED_FUNCTION_OBJ,
+            Object::Closure(..) => CLOSURE_OBJ,
+            Object::BuiltinFn(_) => BUILTIN_OBJ,
+            Object::Array(_) => ARRAY_OBJ,
+            Object::Range { .. } => RANGE_OBJ,
+            Object::Hash(_) => HASH_OBJ,
+            Object::Quote(_) => QUOTE_OBJ,
+            Object::Error(_) => ERROR_OBJ,
+            Object::Null => NULL_OBJ,
         }
+        .to_string()
     }
 
     pub fn get_hash_key(&self) -> Option<HashKey> {
         match self {
-            Object::Integer(integer) => Some(HashKey {
-                kind: "INTEGER".to_string(),
-                value: *integer as u64,
-            }),
-            Object::Boolean(boolean) => Some(HashKey {
-                kind: "BOOLEAN".to_string(),
-                value: match boolean {
-                    false => 0,
-                    true => 1,
-                },
-            }),
-            Object::String(string) => {
-                let mut hasher = DefaultHasher::new();
-                Hash::hash(&string, &mut hasher);
-                Some(HashKey {
-                    kind: "STRING".to_string(),
-                    value: hasher.finish(),
-                })
-            }
+            Object::Integer(integer) => Some(HashKey::Integer(*integer)),
+            Object::Boolean(boolean) => Some(HashKey::Boolean(*boolean)),
+            Object::String(string) => Some(HashKey::String(string.clone())),
             _ => None,
         }
     }
@@ -83,23 +100,115 @@ impl Object {
             _ => true,
         }
     }
+
+    // Like `to_string`, but arrays and hashes put each element on its own line, indented by
+    // two spaces per level of nesting. Empty collections and scalars stay inline.
+    pub fn pretty(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, depth: usize) {
+        let (open, close, items) = match self {
+            Object::Array(elements) if !elements.is_empty() => (
+                "[",
+                "]",
+                elements
+                    .iter()
+                    .map(|element| (None, element))
+                    .collect::<Vec<_>>(),
+            ),
+            Object::Hash(pairs) if !pairs.is_empty() => (
+                "{",
+                "}",
+                pairs
+                    .values()
+                    .map(|pair| (Some(&pair.key), &pair.value))
+                    .collect::<Vec<_>>(),
+            ),
+            Object::Hash(_) => {
+                out.push_str("{}");
+                return;
+            }
+            obj => {
+                out.push_str(&obj.to_string());
+                return;
+            }
+        };
+
+        let indent = "  ".repeat(depth + 1);
+        out.push_str(open);
+        for (i, (key, value)) in items.into_iter().enumerate() {
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            out.push_str(&indent);
+            if let Some(key) = key {
+                out.push_str(&format!("{}: ", key));
+            }
+            value.write_pretty(out, depth + 1);
+        }
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(close);
+    }
+
+    pub fn as_integer(&self) -> Option<Integer> {
+        match self {
+            Object::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Object::Float(float) => Some(*float),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Object::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Object::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Rc<Object>]> {
+        match self {
+            Object::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_hash(&self) -> Option<&HashPairs> {
+        match self {
+            Object::Hash(pairs) => Some(pairs),
+            _ => None,
+        }
+    }
 }
 
 impl Display for Object {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Integer(integer) => write!(f, "{}", integer),
+            Self::Float(float) => write!(f, "{:?}", float),
             Self::Boolean(boolean) => write!(f, "{}", boolean),
             Self::String(string) => write!(f, "{}", string),
             Self::ReturnValue(value) => write!(f, "{}", value),
-            Self::Function {
-                parameters, body, ..
-            } => {
+            Self::Exit(code) => write!(f, "exit({})", code),
+            Self::Function { parameters, .. } => {
                 write!(
                     f,
-                    "fn({}) {{ {} }}",
-                    fmt_identifier_expressions(parameters, ", "),
-                    body
+                    "fn({}) {{ ... }}",
+                    fmt_identifier_expressions(parameters, ", ")
                 )
             }
             Self::CompiledFn(_) => {
@@ -118,21 +227,118 @@ impl Display for Object {
                     .collect::<Vec<String>>()
                     .join(", ")
             ),
+            Self::Range { start, end, step } => write!(f, "range({}, {}, {})", start, end, step),
             Self::Hash(pairs) => write!(
                 f,
                 "[{}]",
                 pairs
-                    .iter()
-                    .map(|(_, v)| format!("{}: {}", v.key, v.value))
+                    .values()
+                    .map(|v| format!("{}: {}", v.key, v.value))
                     .collect::<Vec<String>>()
                     .join(", ")
             ),
+            Self::Quote(node) => write!(f, "QUOTE({})", node),
             Self::Error(msg) => write!(f, "{}", msg),
             Self::Null => write!(f, "null"),
         }
     }
 }
 
+impl From<Integer> for Object {
+    fn from(integer: Integer) -> Self {
+        Object::Integer(integer)
+    }
+}
+
+impl From<f64> for Object {
+    fn from(float: f64) -> Self {
+        Object::Float(float)
+    }
+}
+
+impl From<bool> for Object {
+    fn from(boolean: bool) -> Self {
+        Object::Boolean(boolean)
+    }
+}
+
+impl From<&str> for Object {
+    fn from(string: &str) -> Self {
+        Object::String(string.to_string())
+    }
+}
+
+impl From<String> for Object {
+    fn from(string: String) -> Self {
+        Object::String(string)
+    }
+}
+
+impl From<Vec<Object>> for Object {
+    fn from(elements: Vec<Object>) -> Self {
+        Object::Array(elements.into_iter().map(Rc::new).collect())
+    }
+}
+
+fn conversion_error(expected: &str, found: &Object) -> String {
+    format!("expected {}, found {}", expected, found.get_type_str())
+}
+
+impl TryFrom<Object> for Integer {
+    type Error = String;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::Integer(integer) => Ok(integer),
+            obj => Err(conversion_error(INTEGER_OBJ, &obj)),
+        }
+    }
+}
+
+impl TryFrom<Object> for f64 {
+    type Error = String;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::Float(float) => Ok(float),
+            obj => Err(conversion_error(FLOAT_OBJ, &obj)),
+        }
+    }
+}
+
+impl TryFrom<Object> for bool {
+    type Error = String;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::Boolean(boolean) => Ok(boolean),
+            obj => Err(conversion_error(BOOLEAN_OBJ, &obj)),
+        }
+    }
+}
+
+impl TryFrom<Object> for String {
+    type Error = String;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::String(string) => Ok(string),
+            obj => Err(conversion_error(STRING_OBJ, &obj)),
+        }
+    }
+}
+
+impl TryFrom<Object> for Vec<Object> {
+    type Error = String;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::Array(elements) => Ok(elements.into_iter().map(Rc::unwrap_or_clone).collect()),
+            obj => Err(conversion_error(ARRAY_OBJ, &obj)),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct CompiledFn {
     pub instructions: Instructions,
@@ -150,6 +356,12 @@ impl CompiledFn {
     }
 }
 
+impl Default for CompiledFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Closure {
     pub function: Rc<CompiledFn>,
@@ -165,10 +377,19 @@ impl Closure {
     }
 }
 
+impl Default for Closure {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Keys order by type first (booleans, then integers, then strings) and then by value, so any
+// ordering over keys is total and never depends on insertion or hashing.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
-pub struct HashKey {
-    kind: String,
-    value: u64,
+pub enum HashKey {
+    Boolean(bool),
+    Integer(Integer),
+    String(String),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -177,6 +398,122 @@ pub struct HashPair {
     pub value: Rc<Object>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct HashPairs {
+    entries: Vec<HashPair>,
+    indices: BTreeMap<HashKey, usize>,
+}
+
+impl HashPairs {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            indices: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, hash_key: HashKey, pair: HashPair) {
+        match self.indices.get(&hash_key) {
+            Some(&index) => self.entries[index] = pair,
+            None => {
+                self.indices.insert(hash_key, self.entries.len());
+                self.entries.push(pair);
+            }
+        }
+    }
+
+    pub fn get(&self, hash_key: &HashKey) -> Option<&HashPair> {
+        self.indices
+            .get(hash_key)
+            .map(|&index| &self.entries[index])
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &HashPair> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl PartialEq for HashPairs {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .indices
+                .iter()
+                .all(|(hash_key, &index)| other.get(hash_key) == Some(&self.entries[index]))
+    }
+}
+
+// Arrays of different lengths are unequal without looking at any element.
+pub fn arrays_equal(lhs: &[Rc<Object>], rhs: &[Rc<Object>]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs.iter()).all(|(l, r)| deep_equal(l, r))
+}
+
+pub fn deep_equal(lhs: &Object, rhs: &Object) -> bool {
+    match (lhs, rhs) {
+        (Object::Array(lhs_elements), Object::Array(rhs_elements)) => {
+            arrays_equal(lhs_elements, rhs_elements)
+        }
+        (Object::Hash(lhs_pairs), Object::Hash(rhs_pairs)) => {
+            lhs_pairs.len() == rhs_pairs.len()
+                && lhs_pairs.values().all(|pair| {
+                    pair.key.get_hash_key().is_some_and(|hash_key| {
+                        rhs_pairs
+                            .get(&hash_key)
+                            .is_some_and(|other| deep_equal(&pair.value, &other.value))
+                    })
+                })
+        }
+        // Function environments may contain the function itself, so compare them by identity.
+        (
+            Object::Function {
+                parameters: lhs_parameters,
+                body: lhs_body,
+                env: lhs_env,
+            },
+            Object::Function {
+                parameters: rhs_parameters,
+                body: rhs_body,
+                env: rhs_env,
+            },
+        ) => {
+            lhs_parameters == rhs_parameters && lhs_body == rhs_body && Rc::ptr_eq(lhs_env, rhs_env)
+        }
+        (Object::ReturnValue(lhs_value), Object::ReturnValue(rhs_value)) => {
+            deep_equal(lhs_value, rhs_value)
+        }
+        _ => lhs == rhs,
+    }
+}
+
+pub fn range_len(start: Integer, end: Integer, step: Integer) -> usize {
+    let (start, end, step) = (start as i128, end as i128, step as i128);
+    let span = if step > 0 { end - start } else { start - end };
+    if span <= 0 || step == 0 {
+        return 0;
+    }
+    ((span + step.abs() - 1) / step.abs()) as usize
+}
+
+pub fn range_get(start: Integer, end: Integer, step: Integer, index: usize) -> Option<Integer> {
+    if index >= range_len(start, end, step) {
+        return None;
+    }
+    Some((start as i128 + index as i128 * step as i128) as Integer)
+}
+
+pub fn range_values(start: Integer, end: Integer, step: Integer) -> impl Iterator<Item = Integer> {
+    (0..range_len(start, end, step))
+        .map(move |i| (start as i128 + i as i128 * step as i128) as Integer)
+}
+
 pub fn new_error(message: String) -> Object {
     Object::Error(message)
 }
@@ -188,3 +525,10 @@ pub fn is_truthy(object: &Object) -> bool {
 pub fn get_bool_object(expr: bool) -> Rc<Object> {
     Rc::new(Object::Boolean(expr))
 }
+
+pub fn integer_pow(base: Integer, exponent: Integer) -> Result<Integer, String> {
+    let exponent = u32::try_from(exponent)
+        .map_err(|_| format!("negative exponent not supported: {}", exponent))?;
+    base.checked_pow(exponent)
+        .ok_or_else(|| format!("integer overflow: {} ** {}", base, exponent))
+}
@@ -0,0 +1,308 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        ptr,
+        rc::Rc,
+        time::Duration,
+    };
+
+    use crate::object::{
+        builtins::{
+            builtin_names, get_builtin_fn, set_clock, set_env_access, set_printer, set_sleeper,
+            BUILTINS,
+        },
+        Object,
+    };
+
+    #[test]
+    fn test_builtin_names() {
+        let names = builtin_names();
+
+        for expected in ["len", "first", "last", "rest", "push", "puts"] {
+            assert!(names.contains(&expected), "missing builtin: {}", expected);
+        }
+        for name in names {
+            assert!(
+                get_builtin_fn(name).is_some(),
+                "unresolvable builtin: {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_builtin_fn() {
+        for builtin in BUILTINS.iter() {
+            let resolved = get_builtin_fn(builtin.name).expect("builtin not found");
+            assert!(ptr::eq(resolved, builtin));
+        }
+        assert!(get_builtin_fn("nonexistent").is_none());
+        assert!(get_builtin_fn("").is_none());
+    }
+
+    #[test]
+    fn test_builtin_arity() {
+        for builtin in BUILTINS.iter() {
+            assert!(!builtin.description.is_empty());
+
+            if let Some(arity) = builtin.arity {
+                let args = (0..=arity)
+                    .map(|_| Rc::new(Object::Null))
+                    .collect::<Vec<Rc<Object>>>();
+                assert_eq!(
+                    builtin.call(&args),
+                    Rc::new(Object::Error(format!(
+                        "wrong number of arguments: expected {}, found {}",
+                        arity,
+                        arity + 1
+                    )))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_help() {
+        let help = get_builtin_fn("help").expect("builtin not found");
+
+        let description = help.call(&[Rc::new(Object::String("len".to_string()))]);
+        match description.as_ref() {
+            Object::String(description) => assert!(!description.is_empty()),
+            obj => panic!("expected STRING, found {}", obj.get_type_str()),
+        }
+        assert_eq!(
+            help.call(&[Rc::new(Object::String("nope".to_string()))]),
+            Rc::new(Object::Error("unknown builtin: nope".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_time_now() {
+        let time_now = get_builtin_fn("time_now").expect("builtin not found");
+
+        set_clock(|| 1_700_000_000_000.0);
+        assert_eq!(
+            time_now.call(&[]),
+            Rc::new(Object::Float(1_700_000_000_000.0))
+        );
+    }
+
+    #[test]
+    fn test_sleep() {
+        thread_local! {
+            static SLEPT: Cell<Option<Duration>> = const { Cell::new(None) };
+        }
+        let sleep = get_builtin_fn("sleep").expect("builtin not found");
+
+        set_sleeper(|duration| SLEPT.with(|slept| slept.set(Some(duration))));
+        assert_eq!(
+            sleep.call(&[Rc::new(Object::Integer(250))]),
+            Rc::new(Object::Null)
+        );
+        assert_eq!(SLEPT.with(Cell::take), Some(Duration::from_millis(250)));
+
+        assert_eq!(
+            sleep.call(&[Rc::new(Object::Integer(-1))]),
+            Rc::new(Object::Error(
+                "argument to 'sleep' must not be negative, found -1".to_string()
+            ))
+        );
+        assert_eq!(
+            sleep.call(&[Rc::new(Object::String("1".to_string()))]),
+            Rc::new(Object::Error(
+                "argument to 'sleep' must be INTEGER, found STRING".to_string()
+            ))
+        );
+        assert_eq!(SLEPT.with(Cell::take), None);
+    }
+
+    #[test]
+    fn test_env_get_set() {
+        thread_local! {
+            static VARS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+        }
+        let env_get = get_builtin_fn("env_get").expect("builtin not found");
+        let env_set = get_builtin_fn("env_set").expect("builtin not found");
+        let string = |s: &str| Rc::new(Object::String(s.to_string()));
+
+        set_env_access(
+            |name| VARS.with(|vars| vars.borrow().get(name).cloned()),
+            |name, value| {
+                VARS.with(|vars| {
+                    vars.borrow_mut()
+                        .insert(name.to_string(), value.to_string())
+                });
+            },
+        );
+        VARS.with(|vars| {
+            vars.borrow_mut()
+                .insert("HOME".to_string(), "/home/monkey".to_string())
+        });
+
+        assert_eq!(env_get.call(&[string("HOME")]), string("/home/monkey"));
+        assert_eq!(env_get.call(&[string("EDITOR")]), Rc::new(Object::Null));
+        assert_eq!(
+            env_set.call(&[string("EDITOR"), string("vi")]),
+            Rc::new(Object::Null)
+        );
+        assert_eq!(env_get.call(&[string("EDITOR")]), string("vi"));
+        assert_eq!(
+            env_set.call(&[string("A=B"), string("c")]),
+            Rc::new(Object::Error(
+                "invalid environment variable: A=B=c".to_string()
+            ))
+        );
+        assert_eq!(
+            env_get.call(&[Rc::new(Object::Integer(1))]),
+            Rc::new(Object::Error(
+                "argument to 'env_get' must be STRING, found INTEGER".to_string()
+            ))
+        );
+        assert_eq!(
+            env_set.call(&[string("EDITOR"), Rc::new(Object::Integer(1))]),
+            Rc::new(Object::Error(
+                "arguments to 'env_set' must be STRING, found STRING and INTEGER".to_string()
+            ))
+        );
+        assert_eq!(VARS.with(|vars| vars.borrow().len()), 2);
+    }
+
+    #[test]
+    fn test_puts() {
+        thread_local! {
+            static LINES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+        }
+        let puts = get_builtin_fn("puts").expect("builtin not found");
+
+        set_printer(|line| LINES.with(|lines| lines.borrow_mut().push(line.to_string())));
+        let args = [1, 2, 3].map(|integer| Rc::new(Object::Integer(integer)));
+        assert_eq!(puts.call(&args), Rc::new(Object::Null));
+        assert_eq!(
+            puts.call(&[
+                Rc::new(Object::String("a b".to_string())),
+                Rc::new(Object::Null)
+            ]),
+            Rc::new(Object::Null)
+        );
+        assert_eq!(puts.call(&[]), Rc::new(Object::Null));
+
+        assert_eq!(
+            LINES.with(RefCell::take),
+            vec!["1 2 3".to_string(), "a b null".to_string(), "".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_times() {
+        thread_local! {
+            static LINES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+        }
+        let times = get_builtin_fn("times").expect("builtin not found");
+        let puts = Rc::new(Object::BuiltinFn(
+            get_builtin_fn("puts").expect("builtin not found"),
+        ));
+
+        set_printer(|line| LINES.with(|lines| lines.borrow_mut().push(line.to_string())));
+        assert_eq!(
+            times.call(&[Rc::new(Object::Integer(3)), puts.clone()]),
+            Rc::new(Object::Null)
+        );
+        assert_eq!(
+            times.call(&[Rc::new(Object::Integer(-2)), puts.clone()]),
+            Rc::new(Object::Null)
+        );
+        assert_eq!(
+            LINES.with(RefCell::take),
+            vec!["0".to_string(), "1".to_string(), "2".to_string()]
+        );
+
+        assert_eq!(
+            times.call(&[Rc::new(Object::Integer(1)), Rc::new(Object::Integer(1))]),
+            Rc::new(Object::Error(
+                "second argument to 'times' must be FUNCTION, found INTEGER".to_string()
+            ))
+        );
+        assert_eq!(
+            times.call(&[Rc::new(Object::Float(1.0)), puts]),
+            Rc::new(Object::Error(
+                "first argument to 'times' must be INTEGER, found FLOAT".to_string()
+            ))
+        );
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_read_write_file() {
+        use std::io;
+
+        use crate::object::builtins::{set_file_system, FileSystem};
+
+        #[derive(Default)]
+        struct MemoryFileSystem {
+            files: RefCell<HashMap<String, String>>,
+        }
+
+        impl FileSystem for MemoryFileSystem {
+            fn read(&self, path: &str) -> io::Result<String> {
+                self.files
+                    .borrow()
+                    .get(path)
+                    .cloned()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found"))
+            }
+
+            fn write(&self, path: &str, contents: &str) -> io::Result<()> {
+                if path.ends_with('/') {
+                    return Err(io::Error::other("is a directory"));
+                }
+                self.files
+                    .borrow_mut()
+                    .insert(path.to_string(), contents.to_string());
+                Ok(())
+            }
+        }
+
+        let read_file = get_builtin_fn("read_file").expect("builtin not found");
+        let write_file = get_builtin_fn("write_file").expect("builtin not found");
+        let string = |s: &str| Rc::new(Object::String(s.to_string()));
+
+        let file_system = Rc::new(MemoryFileSystem::default());
+        set_file_system(file_system.clone());
+
+        assert_eq!(
+            write_file.call(&[string("notes.txt"), string("hello")]),
+            Rc::new(Object::Null)
+        );
+        assert_eq!(
+            file_system.files.borrow().get("notes.txt"),
+            Some(&"hello".to_string())
+        );
+        assert_eq!(read_file.call(&[string("notes.txt")]), string("hello"));
+        assert_eq!(
+            read_file.call(&[string("missing.txt")]),
+            Rc::new(Object::Error(
+                "could not read file missing.txt: not found".to_string()
+            ))
+        );
+        assert_eq!(
+            write_file.call(&[string("dir/"), string("")]),
+            Rc::new(Object::Error(
+                "could not write file dir/: is a directory".to_string()
+            ))
+        );
+        assert_eq!(
+            read_file.call(&[Rc::new(Object::Integer(1))]),
+            Rc::new(Object::Error(
+                "argument to 'read_file' must be STRING, found INTEGER".to_string()
+            ))
+        );
+        assert_eq!(
+            write_file.call(&[string("notes.txt"), Rc::new(Object::Integer(1))]),
+            Rc::new(Object::Error(
+                "arguments to 'write_file' must be STRING, found STRING and INTEGER".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+    use crate::{
+        evaluator::{environment::Environment, eval_program},
+        object::{
+            builtins::get_builtin_fn, Closure, CompiledFn, HashKey, HashPair, HashPairs, Integer,
+            Object,
+        },
+        parser::{
+            ast::{BlockStatement, Node, Program},
+            parse_program,
+        },
+    };
+
+    #[test]
+    fn test_get_type_str() {
+        let objs = [
+            Object::Integer(1),
+            Object::Float(1.5),
+            Object::Boolean(true),
+            Object::String("monkey".to_string()),
+            Object::ReturnValue(Rc::new(Object::Integer(1))),
+            Object::Exit(0),
+            Object::Function {
+                parameters: vec![],
+                body: BlockStatement { statements: vec![] },
+                env: Rc::new(RefCell::new(Environment::new())),
+            },
+            Object::CompiledFn(CompiledFn::new()),
+            Object::Closure(Closure::new()),
+            Object::BuiltinFn(get_builtin_fn("len").expect("builtin not found")),
+            Object::Array(vec![]),
+            Object::Range {
+                start: 0,
+                end: 1,
+                step: 1,
+            },
+            Object::Hash(HashPairs::new()),
+            Object::Quote(Rc::new(Node::Program(Program(vec![])))),
+            Object::Error("error".to_string()),
+            Object::Null,
+        ];
+        let expected_type_strs = [
+            "INTEGER",
+            "FLOAT",
+            "BOOLEAN",
+            "STRING",
+            "RETURN_VALUE",
+            "EXIT",
+            "FUNCTION",
+            "COMPILED_FUNCTION",
+            "CLOSURE",
+            "BUILTIN",
+            "ARRAY",
+            "RANGE",
+            "HASH",
+            "QUOTE",
+            "ERROR",
+            "NULL",
+        ];
+
+        for (i, obj) in objs.iter().enumerate() {
+            assert_eq!(obj.get_type_str(), expected_type_strs[i]);
+        }
+
+        let distinct = objs
+            .iter()
+            .map(|obj| obj.get_type_str())
+            .collect::<HashSet<String>>();
+        assert_eq!(distinct.len(), objs.len());
+    }
+
+    #[test]
+    fn test_object_conversions() {
+        assert_eq!(Object::from(5), Object::Integer(5));
+        assert_eq!(Object::from(1.5), Object::Float(1.5));
+        assert_eq!(Object::from(true), Object::Boolean(true));
+        assert_eq!(Object::from("monkey"), Object::String("monkey".to_string()));
+        assert_eq!(
+            Object::from("monkey".to_string()),
+            Object::String("monkey".to_string())
+        );
+        assert_eq!(
+            Object::from(vec![1.into(), "a".into()]),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::String("a".to_string())),
+            ])
+        );
+
+        assert_eq!(Integer::try_from(Object::from(5)), Ok(5));
+        assert_eq!(f64::try_from(Object::from(1.5)), Ok(1.5));
+        assert_eq!(bool::try_from(Object::from(false)), Ok(false));
+        assert_eq!(
+            String::try_from(Object::from("monkey")),
+            Ok("monkey".to_string())
+        );
+        assert_eq!(
+            Vec::<Object>::try_from(Object::from(vec![1.into(), true.into()])),
+            Ok(vec![Object::Integer(1), Object::Boolean(true)])
+        );
+
+        assert_eq!(
+            Integer::try_from(Object::from("5")),
+            Err("expected INTEGER, found STRING".to_string())
+        );
+        assert_eq!(
+            f64::try_from(Object::from(5)),
+            Err("expected FLOAT, found INTEGER".to_string())
+        );
+        assert_eq!(
+            bool::try_from(Object::Null),
+            Err("expected BOOLEAN, found NULL".to_string())
+        );
+        assert_eq!(
+            String::try_from(Object::from(vec![])),
+            Err("expected STRING, found ARRAY".to_string())
+        );
+        assert_eq!(
+            Vec::<Object>::try_from(Object::from(1)),
+            Err("expected ARRAY, found INTEGER".to_string())
+        );
+    }
+
+    #[test]
+    fn test_object_accessors() {
+        let array = Object::from(vec![1.into()]);
+        let hash = Object::Hash(HashPairs::new());
+
+        assert_eq!(Object::Integer(5).as_integer(), Some(5));
+        assert_eq!(Object::Float(5.0).as_integer(), None);
+        assert_eq!(Object::Float(1.5).as_float(), Some(1.5));
+        assert_eq!(Object::Integer(1).as_float(), None);
+        assert_eq!(Object::Boolean(true).as_bool(), Some(true));
+        assert_eq!(Object::Null.as_bool(), None);
+        assert_eq!(Object::from("monkey").as_string(), Some("monkey"));
+        assert_eq!(Object::Integer(1).as_string(), None);
+        assert_eq!(array.as_array(), Some(&[Rc::new(Object::Integer(1))][..]));
+        assert_eq!(hash.as_array(), None);
+        assert_eq!(hash.as_hash(), Some(&HashPairs::new()));
+        assert_eq!(array.as_hash(), None);
+    }
+
+    #[test]
+    fn test_display_functions() {
+        let program = parse_program("fn(x, y) { x + y }").expect("error occurred while parsing");
+        let env = Rc::new(RefCell::new(Environment::new()));
+        assert_eq!(
+            eval_program(&program, env).to_string(),
+            "fn(x, y) { ... }".to_string()
+        );
+
+        let len = Object::BuiltinFn(get_builtin_fn("len").expect("builtin not found"));
+        assert_eq!(len.to_string(), "builtin function".to_string());
+    }
+
+    #[test]
+    fn test_hash_key_order() {
+        let keys = [
+            Object::String("b".to_string()),
+            Object::Integer(10),
+            Object::Boolean(true),
+            Object::String("a".to_string()),
+            Object::Integer(-1),
+            Object::Boolean(false),
+        ];
+        let build = |keys: &mut dyn Iterator<Item = &Object>| {
+            let mut pairs = HashPairs::new();
+            for key in keys {
+                pairs.insert(
+                    key.get_hash_key().expect("unusable as hash key"),
+                    HashPair {
+                        key: Rc::new(key.clone()),
+                        value: Rc::new(Object::Null),
+                    },
+                );
+            }
+            pairs
+        };
+        let forward = build(&mut keys.iter());
+        let backward = build(&mut keys.iter().rev());
+
+        let mut sorted = keys
+            .iter()
+            .map(|key| key.get_hash_key().expect("unusable as hash key"))
+            .collect::<Vec<HashKey>>();
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            [
+                HashKey::Boolean(false),
+                HashKey::Boolean(true),
+                HashKey::Integer(-1),
+                HashKey::Integer(10),
+                HashKey::String("a".to_string()),
+                HashKey::String("b".to_string()),
+            ]
+        );
+
+        let inserted = forward
+            .values()
+            .map(|pair| pair.key.as_ref().clone())
+            .collect::<Vec<Object>>();
+        assert_eq!(inserted, keys);
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn test_pretty() {
+        let inputs = [
+            "5",
+            "[]",
+            "{}",
+            "[1, 2]",
+            "{\"name\": \"monkey\", \"tags\": [1, [2, 3], []], \"meta\": {\"ok\": true}}",
+        ];
+        let expected = [
+            "5",
+            "[]",
+            "{}",
+            "[\n  1,\n  2\n]",
+            "{
+  name: monkey,
+  tags: [
+    1,
+    [
+      2,
+      3
+    ],
+    []
+  ],
+  meta: {
+    ok: true
+  }
+}",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = parse_program(input).expect("error occurred while parsing");
+            let env = Rc::new(RefCell::new(Environment::new()));
+            assert_eq!(eval_program(&program, env).pretty(), expected[i]);
+        }
+
+        let program =
+            parse_program("pretty([1, {\"a\": 2}])").expect("error occurred while parsing");
+        let env = Rc::new(RefCell::new(Environment::new()));
+        assert_eq!(
+            *eval_program(&program, env),
+            Object::String("[\n  1,\n  {\n    a: 2\n  }\n]".to_string())
+        );
+    }
+}
@@ -1,11 +1,8 @@
-use std::{
-    collections::BTreeMap,
-    fmt::{Display, Formatter, Result},
-};
+use std::fmt::{Display, Formatter, Result};
 
-use crate::lexer::token::Token;
+use crate::{lexer::token::Token, object::Integer};
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Node {
     Program(Program),
     Statement(Statement),
@@ -22,7 +19,7 @@ impl Display for Node {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Program(pub Vec<Statement>);
 
 impl Display for Program {
@@ -31,11 +28,13 @@ impl Display for Program {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Statement {
     Let(LetStatement),
+    Const(LetStatement),
     Return(ReturnStatement),
     Expression(ExpressionStatement),
+    Block(BlockStatement),
 }
 
 impl Display for Statement {
@@ -44,29 +43,33 @@ impl Display for Statement {
             Self::Let(stmt) => {
                 write!(f, "let {} = {};", stmt.identifier, stmt.value)
             }
+            Self::Const(stmt) => {
+                write!(f, "const {} = {};", stmt.identifier, stmt.value)
+            }
             Self::Return(stmt) => write!(f, "return {};", stmt.value),
             Self::Expression(stmt) => write!(f, "{};", stmt.expr),
+            Self::Block(block) => write!(f, "{{ {} }}", block),
         }
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct LetStatement {
     pub identifier: IdentifierExpression,
     pub value: Expression,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct ReturnStatement {
     pub value: Expression,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct ExpressionStatement {
     pub expr: Expression,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct BlockStatement {
     pub statements: Vec<Statement>,
 }
@@ -77,20 +80,26 @@ impl Display for BlockStatement {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Expression {
     Identifier(IdentifierExpression),
     Integer(IntegerExpression),
+    Float(FloatExpression),
     String(StringExpression),
     Prefix(PrefixExpression),
     Infix(InfixExpression),
+    Comparison(ComparisonExpression),
     Boolean(BooleanExpression),
     If(IfExpression),
+    Do(DoExpression),
+    Try(TryExpression),
+    Match(MatchExpression),
     FnLiteral(FnLiteralExpression),
     ArrayLiteral(ArrayLiteralExpression),
     HashLiteral(HashLiteralExpression),
     Call(CallExpression),
     Index(IndexExpression),
+    Placeholder,
 }
 
 impl Display for Expression {
@@ -98,7 +107,8 @@ impl Display for Expression {
         match self {
             Expression::Identifier(expr) => write!(f, "{}", expr),
             Expression::Integer(expr) => write!(f, "{}", expr.value),
-            Expression::String(expr) => write!(f, "{}", expr.value),
+            Expression::Float(expr) => write!(f, "{:?}", expr.value),
+            Expression::String(expr) => write!(f, "\"{}\"", expr.value),
             Expression::Prefix(expr) => {
                 write!(f, "({}{})", expr.operator.get_literal(), expr.operand)
             }
@@ -109,23 +119,54 @@ impl Display for Expression {
                 expr.operator.get_literal(),
                 expr.rhs
             ),
+            Expression::Comparison(expr) => {
+                write!(f, "({}", expr.operands[0])?;
+                for (operator, operand) in expr.operators.iter().zip(&expr.operands[1..]) {
+                    write!(f, " {} {}", operator.get_literal(), operand)?;
+                }
+                write!(f, ")")
+            }
             Expression::Boolean(expr) => write!(f, "{}", expr.value),
-            Expression::If(expr) => match &expr.alternative {
-                Some(alternative) => write!(
-                    f,
-                    "if {} {{ {} }} else {{ {} }}",
-                    expr.condition, expr.consequence, alternative
-                ),
-                None => write!(f, "if {} {{ {} }}", expr.condition, expr.consequence),
-            },
+            Expression::If(expr) => {
+                // Prefix, infix and index expressions already display inside parentheses.
+                let condition = match expr.condition.as_ref() {
+                    Expression::Prefix(_) | Expression::Infix(_) | Expression::Index(_) => {
+                        expr.condition.to_string()
+                    }
+                    condition => format!("({})", condition),
+                };
+                match &expr.alternative {
+                    Some(alternative) => write!(
+                        f,
+                        "if {} {{ {} }} else {{ {} }}",
+                        condition, expr.consequence, alternative
+                    ),
+                    None => write!(f, "if {} {{ {} }}", condition, expr.consequence),
+                }
+            }
+            Expression::Do(expr) => write!(f, "do {{ {} }}", expr.body),
+            Expression::Try(expr) => write!(
+                f,
+                "try {{ {} }} catch ({}) {{ {} }}",
+                expr.body, expr.error, expr.handler
+            ),
+            Expression::Match(expr) => {
+                let mut arms = expr
+                    .arms
+                    .iter()
+                    .map(|arm| match &arm.guard {
+                        Some(guard) => format!("case {} if {} => {}", arm.value, guard, arm.body),
+                        None => format!("case {} => {}", arm.value, arm.body),
+                    })
+                    .collect::<Vec<String>>();
+                if let Some(default) = &expr.default {
+                    arms.push(format!("default => {}", default));
+                }
+                write!(f, "match {} {{ {} }}", expr.subject, arms.join(", "))
+            }
             Expression::FnLiteral(expr) => write!(
                 f,
-                "{} fn({}) {{ {} }}",
-                if expr.name.is_empty() {
-                    "".to_string()
-                } else {
-                    format!("<{}>", expr.name)
-                },
+                "fn({}) {{ {} }}",
                 fmt_identifier_expressions(&expr.parameters, ", "),
                 expr.body
             ),
@@ -148,11 +189,12 @@ impl Display for Expression {
                 fmt_expressions(&expr.arguments, ", "),
             ),
             Expression::Index(expr) => write!(f, "({}[{}])", expr.identifier, expr.index),
+            Expression::Placeholder => write!(f, "_"),
         }
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct IdentifierExpression {
     pub name: String,
 }
@@ -163,65 +205,104 @@ impl Display for IdentifierExpression {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct IntegerExpression {
-    pub value: i32,
+    pub value: Integer,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct FloatExpression {
+    pub value: f64,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct StringExpression {
     pub value: String,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct PrefixExpression {
     pub operator: Token,
     pub operand: Box<Expression>,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct InfixExpression {
     pub operator: Token,
     pub lhs: Box<Expression>,
     pub rhs: Box<Expression>,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+// A chain such as `a < b < c`, meaning `a < b` and `b < c`; `operators[i]` sits between
+// `operands[i]` and `operands[i + 1]`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ComparisonExpression {
+    pub operands: Vec<Expression>,
+    pub operators: Vec<Token>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub struct BooleanExpression {
     pub value: bool,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct IfExpression {
     pub condition: Box<Expression>,
     pub consequence: BlockStatement,
     pub alternative: Option<BlockStatement>,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+pub struct DoExpression {
+    pub body: BlockStatement,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct TryExpression {
+    pub body: BlockStatement,
+    pub error: IdentifierExpression,
+    pub handler: BlockStatement,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct MatchExpression {
+    pub subject: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+    pub default: Option<Box<Expression>>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct MatchArm {
+    pub value: Expression,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub struct FnLiteralExpression {
     pub name: String,
     pub parameters: Vec<IdentifierExpression>,
     pub body: BlockStatement,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct ArrayLiteralExpression {
     pub elements: Vec<Expression>,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct HashLiteralExpression {
-    pub pairs: BTreeMap<Expression, Expression>,
+    pub pairs: Vec<(Expression, Expression)>,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct CallExpression {
     pub function: Box<Expression>,
     pub arguments: Vec<Expression>,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct IndexExpression {
     pub identifier: Box<Expression>,
     pub index: Box<Expression>,
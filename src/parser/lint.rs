@@ -0,0 +1,183 @@
+use super::{
+    ast::*,
+    visitor::{walk_block_statement, Visitor},
+};
+
+struct Binding {
+    name: String,
+    order: usize,
+    used: bool,
+    reported: bool,
+}
+
+#[derive(Default)]
+struct Scope {
+    bindings: Vec<Binding>,
+    // Names used in a function body before any visible binding was declared. Functions run after
+    // the scopes around them are complete, so these are resolved as each scope ends.
+    unresolved: Vec<String>,
+}
+
+// Scopes mirror the evaluator: functions, `if`/`do`/`try` blocks, catch handlers and match arms
+// that bind an identifier each get their own, so inner bindings shadow outer ones until the block
+// ends.
+#[derive(Default)]
+struct UnusedLinter {
+    scopes: Vec<Scope>,
+    declared: usize,
+    fn_depth: usize,
+    unused: Vec<(usize, String)>,
+}
+
+impl UnusedLinter {
+    fn enter_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    fn leave_scope(&mut self) {
+        if let Some(mut scope) = self.scopes.pop() {
+            for name in scope.unresolved {
+                match scope
+                    .bindings
+                    .iter_mut()
+                    .rev()
+                    .find(|binding| binding.name == name)
+                {
+                    Some(binding) => binding.used = true,
+                    None => {
+                        if let Some(outer) = self.scopes.last_mut() {
+                            outer.unresolved.push(name);
+                        }
+                    }
+                }
+            }
+            for binding in scope.bindings {
+                if binding.reported && !binding.used {
+                    self.unused
+                        .push((binding.order, format!("unused variable: {}", binding.name)));
+                }
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, reported: bool) {
+        self.declared += 1;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.bindings.push(Binding {
+                name: name.to_owned(),
+                order: self.declared,
+                used: false,
+                reported,
+            });
+        }
+    }
+
+    fn mark_used(&mut self, name: &str) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(binding) = scope
+                .bindings
+                .iter_mut()
+                .rev()
+                .find(|binding| binding.name == name)
+            {
+                binding.used = true;
+                return;
+            }
+        }
+        if self.fn_depth > 0 {
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.unresolved.push(name.to_owned());
+            }
+        }
+    }
+}
+
+impl Visitor for UnusedLinter {
+    fn visit_let_statement(&mut self, stmt: &LetStatement) {
+        // Functions may call themselves, so they are in scope of their own body.
+        if let Expression::FnLiteral(_) = stmt.value {
+            self.declare(&stmt.identifier.name, true);
+            self.visit_expression(&stmt.value);
+        } else {
+            self.visit_expression(&stmt.value);
+            self.declare(&stmt.identifier.name, true);
+        }
+    }
+
+    fn visit_const_statement(&mut self, stmt: &LetStatement) {
+        self.visit_expression(&stmt.value);
+        self.declare(&stmt.identifier.name, false);
+    }
+
+    fn visit_block_statement(&mut self, block: &BlockStatement) {
+        self.enter_scope();
+        walk_block_statement(self, block);
+        self.leave_scope();
+    }
+
+    fn visit_identifier(&mut self, expr: &IdentifierExpression) {
+        self.mark_used(&expr.name);
+    }
+
+    fn visit_try(&mut self, expr: &TryExpression) {
+        self.visit_block_statement(&expr.body);
+
+        self.enter_scope();
+        self.declare(&expr.error.name, false);
+        walk_block_statement(self, &expr.handler);
+        self.leave_scope();
+    }
+
+    fn visit_match(&mut self, expr: &MatchExpression) {
+        self.visit_expression(&expr.subject);
+        for arm in expr.arms.iter() {
+            match &arm.value {
+                Expression::Identifier(identifier) => {
+                    self.enter_scope();
+                    self.declare(&identifier.name, false);
+                    if let Some(guard) = &arm.guard {
+                        self.visit_expression(guard);
+                    }
+                    self.visit_expression(&arm.body);
+                    self.leave_scope();
+                }
+                value => {
+                    self.visit_expression(value);
+                    if let Some(guard) = &arm.guard {
+                        self.visit_expression(guard);
+                    }
+                    self.visit_expression(&arm.body);
+                }
+            }
+        }
+        if let Some(default) = &expr.default {
+            self.visit_expression(default);
+        }
+    }
+
+    fn visit_fn_literal(&mut self, expr: &FnLiteralExpression) {
+        self.enter_scope();
+        for parameter in expr.parameters.iter() {
+            self.declare(&parameter.name, false);
+        }
+        self.fn_depth += 1;
+        walk_block_statement(self, &expr.body);
+        self.fn_depth -= 1;
+        self.leave_scope();
+    }
+}
+
+pub fn lint_unused(program: &Program) -> Vec<String> {
+    let mut linter = UnusedLinter::default();
+
+    linter.enter_scope();
+    linter.visit_program(program);
+    linter.leave_scope();
+
+    linter.unused.sort_by_key(|(order, _)| *order);
+    linter
+        .unused
+        .into_iter()
+        .map(|(_, message)| message)
+        .collect()
+}
@@ -1,25 +1,36 @@
-use std::collections::BTreeMap;
-
-use crate::{lexer::token::Token, lexer::Lexer, parser::ast::*};
+use crate::{
+    lexer::token::{Position, Token},
+    lexer::Lexer,
+    parser::ast::*,
+};
 
 pub mod ast;
+pub mod lint;
+mod test_lint;
 mod test_parser;
+mod test_visitor;
+pub mod visitor;
 
 const LOWEST: usize = 1;
 const EQUALS: usize = 2;
 const LESSGREATER: usize = 3;
-const SUM: usize = 4;
-const PRODUCT: usize = 5;
-const PREFIX: usize = 6;
-const CALL: usize = 7;
-const INDEX: usize = 8;
+// Between comparison and arithmetic, as in Elixir: `x |> f == y` compares the piped result.
+const PIPE: usize = 4;
+const SUM: usize = 5;
+const PRODUCT: usize = 6;
+const PREFIX: usize = 7;
+const POWER: usize = 8;
+const CALL: usize = 9;
+const INDEX: usize = 10;
 
 fn get_precedence(token: &Token) -> usize {
     match token {
+        Token::Pipe => PIPE,
         Token::Equal | Token::NotEqual => EQUALS,
         Token::LessThan | Token::GreaterThan => LESSGREATER,
         Token::Plus | Token::Minus => SUM,
         Token::Asterisk | Token::Slash => PRODUCT,
+        Token::Power => POWER,
         Token::LParen => CALL,
         Token::LBracket => INDEX,
         _ => LOWEST,
@@ -33,12 +44,16 @@ fn get_prefix_fn(token: &Token) -> Option<PrefixParseFn> {
     match token {
         Token::Identifier(_) => Some(Parser::parse_identifier_expression),
         Token::Integer(_) => Some(Parser::parse_integer_expression),
+        Token::Float(_) => Some(Parser::parse_float_expression),
         Token::Boolean(_) => Some(Parser::parse_boolean_expression),
         Token::String(_) => Some(Parser::parse_string_literal_expression),
         Token::Bang => Some(Parser::parse_prefix_expression),
         Token::Minus => Some(Parser::parse_prefix_expression),
         Token::LParen => Some(Parser::parse_grouped_expression),
         Token::If => Some(Parser::parse_if_expression),
+        Token::Do => Some(Parser::parse_do_expression),
+        Token::Try => Some(Parser::parse_try_expression),
+        Token::Match => Some(Parser::parse_match_expression),
         Token::Function => Some(Parser::parse_fn_literal_expression),
         Token::LBracket => Some(Parser::parse_array_literal_expression),
         Token::LBrace => Some(Parser::parse_hash_literal_expression),
@@ -52,12 +67,14 @@ fn get_infix_fn(token: &Token) -> Option<InfixParseFn> {
         Token::Minus => Some(Parser::parse_infix_expression),
         Token::Asterisk => Some(Parser::parse_infix_expression),
         Token::Slash => Some(Parser::parse_infix_expression),
+        Token::Power => Some(Parser::parse_infix_expression),
         Token::Equal => Some(Parser::parse_infix_expression),
         Token::NotEqual => Some(Parser::parse_infix_expression),
-        Token::LessThan => Some(Parser::parse_infix_expression),
-        Token::GreaterThan => Some(Parser::parse_infix_expression),
+        Token::LessThan => Some(Parser::parse_comparison_expression),
+        Token::GreaterThan => Some(Parser::parse_comparison_expression),
         Token::LParen => Some(Parser::parse_call_expression),
         Token::LBracket => Some(Parser::parse_index_expression),
+        Token::Pipe => Some(Parser::parse_pipe_expression),
         _ => None,
     }
 }
@@ -71,25 +88,40 @@ pub fn eprint_parse_errors(errs: &ParseErrors) {
     }
 }
 
-fn fmt_token_error(expected: &Token, result: &Token) -> ParseError {
+fn fmt_token_error(expected: &Token, result: &Token, position: &Position) -> ParseError {
     format!(
-        "expected {}, found {}",
+        "expected {}, found {} ({})",
         expected.get_literal(),
-        result.get_literal()
+        result.get_literal(),
+        position
     )
 }
 
 pub fn parse(input: &str) -> Result<Node, ParseErrors> {
+    parse_program(input).map(Node::Program)
+}
+
+pub fn parse_program(input: &str) -> Result<Program, ParseErrors> {
     let lexer = Lexer::new(input);
     let mut parser = Parser::new(lexer);
-    parser.parse_program().map(Node::Program)
+    parser.parse_program()
+}
+
+pub fn parse_program_semicolon_optional(input: &str) -> Result<Program, ParseErrors> {
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new_semicolon_optional(lexer);
+    parser.parse_program()
 }
 
 pub struct Parser {
     lexer: Lexer,
     curr_token: Token,
     peek_token: Token,
+    curr_position: Position,
+    peek_position: Position,
     errors: Vec<ParseError>,
+    semicolon_optional: bool,
+    function_depth: usize,
 }
 
 impl Parser {
@@ -98,7 +130,11 @@ impl Parser {
             lexer,
             curr_token: Token::Unknown,
             peek_token: Token::Unknown,
+            curr_position: Position::default(),
+            peek_position: Position::default(),
             errors: Vec::new(),
+            semicolon_optional: false,
+            function_depth: 0,
         };
 
         parser.next_token();
@@ -107,6 +143,14 @@ impl Parser {
         parser
     }
 
+    // Ends expressions at line breaks, so `f(x)` and `(y)` on separate lines are two statements
+    // rather than the call `f(x)(y)`. Operators continuing an expression must end the line.
+    pub fn new_semicolon_optional(lexer: Lexer) -> Self {
+        let mut parser = Parser::new(lexer);
+        parser.semicolon_optional = true;
+        parser
+    }
+
     pub fn parse_program(&mut self) -> Result<Program, ParseErrors> {
         let mut program: Program = Program(Vec::new());
 
@@ -127,17 +171,64 @@ impl Parser {
 
     fn parse_statement(&mut self) -> Result<Statement, ParseError> {
         match self.curr_token {
-            Token::Let => self.parse_let_statement(),
+            Token::Let => self.parse_let_statement().map(Statement::Let),
+            Token::Const => self.parse_let_statement().map(Statement::Const),
             Token::Return => self.parse_return_statement(),
+            Token::LBrace => self.parse_brace_statement(),
             _ => self.parse_expression_statement(),
         }
     }
 
-    fn parse_let_statement(&mut self) -> Result<Statement, ParseError> {
-        let mut name = String::new();
+    // At statement position `{` may open either a hash literal or a block. `{}` is a hash, as is
+    // anything whose first expression is followed by `:` or `,`; everything else, including
+    // `{x}`, is a block.
+    fn parse_brace_statement(&mut self) -> Result<Statement, ParseError> {
+        let first = match self.peek_token {
+            Token::RBrace => return self.parse_expression_statement(),
+            Token::Let | Token::Const | Token::Return | Token::LBrace => None,
+            _ => {
+                self.next_token();
+                Some(self.parse_expression(LOWEST)?)
+            }
+        };
+
+        let first = match first {
+            Some(key) if matches!(self.peek_token, Token::Colon | Token::Comma) => {
+                let hash = self.parse_hash_literal_rest(Some(key))?;
+                let expr = self.parse_infix_expressions(hash, LOWEST)?;
+                if self.peek_token == Token::Semicolon {
+                    self.next_token();
+                }
+                return Ok(Statement::Expression(ExpressionStatement { expr }));
+            }
+            Some(expr) => {
+                if self.peek_token == Token::Semicolon {
+                    self.next_token();
+                }
+                vec![Statement::Expression(ExpressionStatement { expr })]
+            }
+            None => Vec::new(),
+        };
+
+        let block = self.parse_block_statement_rest(first);
+        if self.curr_token != Token::RBrace {
+            return Err(fmt_token_error(
+                &Token::RBrace,
+                &self.curr_token,
+                &self.curr_position,
+            ));
+        }
+        if self.peek_token == Token::Semicolon {
+            self.next_token();
+        }
+
+        Ok(Statement::Block(block))
+    }
+
+    fn parse_let_statement(&mut self) -> Result<LetStatement, ParseError> {
         match &self.peek_token {
             Token::Identifier(identifier) => {
-                name = identifier.to_owned();
+                let name = identifier.to_owned();
                 self.next_token();
 
                 self.expect_peek(&Token::Assign)?;
@@ -154,19 +245,23 @@ impl Parser {
                     self.next_token();
                 }
 
-                Ok(Statement::Let(LetStatement {
+                Ok(LetStatement {
                     identifier: IdentifierExpression { name },
                     value,
-                }))
+                })
             }
             token => Err(format!(
-                "expected identifier, found {}",
-                token.get_literal()
+                "expected identifier, found {} ({})",
+                token.get_literal(),
+                self.peek_position
             )),
         }
     }
 
+    // `return` is only allowed in function bodies. The value is still parsed so that the rest of
+    // the statement doesn't produce errors of its own.
     fn parse_return_statement(&mut self) -> Result<Statement, ParseError> {
+        let position = self.curr_position;
         self.next_token();
 
         let value = self.parse_expression(LOWEST)?;
@@ -175,6 +270,9 @@ impl Parser {
             self.next_token();
         }
 
+        if self.function_depth == 0 {
+            return Err(format!("return outside of function ({})", position));
+        }
         Ok(Statement::Return(ReturnStatement { value }))
     }
 
@@ -189,13 +287,16 @@ impl Parser {
     }
 
     fn parse_block_statement(&mut self) -> BlockStatement {
-        let mut statements = Vec::new();
+        self.parse_block_statement_rest(Vec::new())
+    }
 
+    fn parse_block_statement_rest(&mut self, mut statements: Vec<Statement>) -> BlockStatement {
         self.next_token();
 
         while self.curr_token != Token::RBrace && self.curr_token != Token::Eof {
-            if let Ok(stmt) = self.parse_statement() {
-                statements.push(stmt);
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(err) => self.errors.push(err),
             }
             self.next_token();
         }
@@ -206,26 +307,38 @@ impl Parser {
     fn parse_expression(&mut self, precedence: usize) -> Result<Expression, ParseError> {
         match get_prefix_fn(&self.curr_token) {
             Some(prefix_fn) => {
-                let mut lhs = prefix_fn(self)?;
-
-                while self.peek_token != Token::Semicolon
-                    && precedence < get_precedence(&self.peek_token)
-                {
-                    if let Some(infix_fn) = get_infix_fn(&self.peek_token) {
-                        self.next_token();
-                        lhs = infix_fn(self, Box::new(lhs.clone()))?;
-                    } else {
-                        return Ok(lhs);
-                    }
-                }
+                let lhs = prefix_fn(self)?;
+                self.parse_infix_expressions(lhs, precedence)
+            }
+            None => match &self.curr_token {
+                Token::Illegal(message) => Err(format!("{} ({})", message, self.curr_position)),
+                token => Err(format!(
+                    "no prefix function found for {} ({})",
+                    token.get_literal(),
+                    self.curr_position
+                )),
+            },
+        }
+    }
 
-                Ok(lhs)
+    fn parse_infix_expressions(
+        &mut self,
+        mut lhs: Expression,
+        precedence: usize,
+    ) -> Result<Expression, ParseError> {
+        while self.peek_token != Token::Semicolon
+            && !self.peek_starts_statement()
+            && precedence < get_precedence(&self.peek_token)
+        {
+            if let Some(infix_fn) = get_infix_fn(&self.peek_token) {
+                self.next_token();
+                lhs = infix_fn(self, Box::new(lhs.clone()))?;
+            } else {
+                return Ok(lhs);
             }
-            None => Err(format!(
-                "no prefix function found for {}",
-                self.curr_token.get_literal()
-            )),
         }
+
+        Ok(lhs)
     }
 
     fn parse_identifier_expression(&mut self) -> Result<Expression, ParseError> {
@@ -234,12 +347,24 @@ impl Parser {
         }))
     }
 
+    fn parse_float_expression(&mut self) -> Result<Expression, ParseError> {
+        match self.curr_token {
+            Token::Float(value) => Ok(Expression::Float(FloatExpression { value })),
+            _ => Err(format!(
+                "expected float, found {} ({})",
+                self.curr_token.get_literal(),
+                self.curr_position
+            )),
+        }
+    }
+
     fn parse_integer_expression(&mut self) -> Result<Expression, ParseError> {
         match self.curr_token.get_literal().parse() {
             Ok(int) => Ok(Expression::Integer(IntegerExpression { value: int })),
             Err(_) => Err(format!(
-                "expected integer, found {}",
-                self.curr_token.get_literal()
+                "expected integer, found {} ({})",
+                self.curr_token.get_literal(),
+                self.curr_position
             )),
         }
     }
@@ -257,7 +382,12 @@ impl Parser {
 
     fn parse_infix_expression(&mut self, lhs: Box<Expression>) -> Result<Expression, ParseError> {
         let operator = self.curr_token.clone();
-        let precedence = get_precedence(&self.curr_token);
+        let precedence = match operator {
+            // `**` binds tighter than prefix operators and is right-associative, so
+            // `-2 ** 2` is `-(2 ** 2)` and `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+            Token::Power => POWER - 1,
+            _ => get_precedence(&self.curr_token),
+        };
         self.next_token();
         let rhs = self.parse_expression(precedence)?;
 
@@ -268,12 +398,77 @@ impl Parser {
         }))
     }
 
+    // `x |> f(a)` desugars to `f(x, a)` and `x |> f` to `f(x)`, so evaluation needs no support.
+    // A placeholder picks the argument position instead: `x |> f(a, _)` is `f(a, x)`.
+    #[allow(clippy::boxed_local)]
+    fn parse_pipe_expression(&mut self, lhs: Box<Expression>) -> Result<Expression, ParseError> {
+        self.next_token();
+        let rhs = self.parse_expression(PIPE)?;
+
+        Ok(match rhs {
+            Expression::Call(mut call) => {
+                match call
+                    .arguments
+                    .iter_mut()
+                    .find(|arg| **arg == Expression::Placeholder)
+                {
+                    Some(placeholder) => *placeholder = *lhs,
+                    None => call.arguments.insert(0, *lhs),
+                }
+                Expression::Call(call)
+            }
+            function => Expression::Call(CallExpression {
+                function: Box::new(function),
+                arguments: vec![*lhs],
+            }),
+        })
+    }
+
+    // `a < b < c` parses as one chain rather than `(a < b) < c`. A parenthesized `(a < b) < c`
+    // keeps its plain meaning.
+    fn parse_comparison_expression(
+        &mut self,
+        lhs: Box<Expression>,
+    ) -> Result<Expression, ParseError> {
+        let operator = self.curr_token.clone();
+        self.next_token();
+        let rhs = self.parse_expression(LESSGREATER)?;
+
+        if !self.peek_continues_comparison() {
+            return Ok(Expression::Infix(InfixExpression {
+                operator,
+                lhs,
+                rhs: Box::new(rhs),
+            }));
+        }
+
+        let mut operators = vec![operator];
+        let mut operands = vec![*lhs, rhs];
+        while self.peek_continues_comparison() {
+            self.next_token();
+            operators.push(self.curr_token.clone());
+            self.next_token();
+            operands.push(self.parse_expression(LESSGREATER)?);
+        }
+
+        Ok(Expression::Comparison(ComparisonExpression {
+            operands,
+            operators,
+        }))
+    }
+
+    fn peek_continues_comparison(&self) -> bool {
+        matches!(self.peek_token, Token::LessThan | Token::GreaterThan)
+            && !self.peek_starts_statement()
+    }
+
     fn parse_boolean_expression(&mut self) -> Result<Expression, ParseError> {
         match self.curr_token.get_literal().parse() {
             Ok(boolean) => Ok(Expression::Boolean(BooleanExpression { value: boolean })),
             Err(_) => Err(format!(
-                "expected boolean, found {}",
-                self.curr_token.get_literal()
+                "expected boolean, found {} ({})",
+                self.curr_token.get_literal(),
+                self.curr_position
             )),
         }
     }
@@ -315,6 +510,102 @@ impl Parser {
         }))
     }
 
+    fn parse_do_expression(&mut self) -> Result<Expression, ParseError> {
+        self.expect_peek(&Token::LBrace)?;
+
+        let body = self.parse_block_statement();
+
+        Ok(Expression::Do(DoExpression { body }))
+    }
+
+    fn parse_try_expression(&mut self) -> Result<Expression, ParseError> {
+        self.expect_peek(&Token::LBrace)?;
+        let body = self.parse_block_statement();
+
+        self.expect_peek(&Token::Catch)?;
+        self.expect_peek(&Token::LParen)?;
+        let error = match &self.peek_token {
+            Token::Identifier(name) => IdentifierExpression {
+                name: name.to_owned(),
+            },
+            token => {
+                return Err(format!(
+                    "expected identifier, found {} ({})",
+                    token.get_literal(),
+                    self.peek_position
+                ))
+            }
+        };
+        self.next_token();
+        self.expect_peek(&Token::RParen)?;
+
+        self.expect_peek(&Token::LBrace)?;
+        let handler = self.parse_block_statement();
+
+        Ok(Expression::Try(TryExpression {
+            body,
+            error,
+            handler,
+        }))
+    }
+
+    fn parse_match_expression(&mut self) -> Result<Expression, ParseError> {
+        self.next_token();
+        let subject = Box::new(self.parse_expression(LOWEST)?);
+
+        self.expect_peek(&Token::LBrace)?;
+
+        let mut arms = Vec::new();
+        let mut default = None;
+        while self.peek_token != Token::RBrace {
+            self.next_token();
+            match &self.curr_token {
+                Token::Case => {
+                    self.next_token();
+                    let value = self.parse_expression(LOWEST)?;
+                    let guard = if self.peek_token == Token::If {
+                        self.next_token();
+                        self.next_token();
+                        Some(self.parse_expression(LOWEST)?)
+                    } else {
+                        None
+                    };
+                    self.expect_peek(&Token::FatArrow)?;
+                    self.next_token();
+                    let body = self.parse_expression(LOWEST)?;
+                    arms.push(MatchArm { value, guard, body });
+                }
+                Token::Default if default.is_none() => {
+                    self.expect_peek(&Token::FatArrow)?;
+                    self.next_token();
+                    default = Some(Box::new(self.parse_expression(LOWEST)?));
+                }
+                Token::Default => {
+                    return Err(format!("duplicate default arm ({})", self.curr_position))
+                }
+                token => {
+                    return Err(format!(
+                        "expected case or default, found {} ({})",
+                        token.get_literal(),
+                        self.curr_position
+                    ))
+                }
+            }
+
+            if self.peek_token != Token::RBrace {
+                self.expect_peek(&Token::Comma)?;
+            }
+        }
+
+        self.expect_peek(&Token::RBrace)?;
+
+        Ok(Expression::Match(MatchExpression {
+            subject,
+            arms,
+            default,
+        }))
+    }
+
     fn parse_fn_literal_expression(&mut self) -> Result<Expression, ParseError> {
         self.expect_peek(&Token::LParen)?;
 
@@ -322,7 +613,9 @@ impl Parser {
 
         self.expect_peek(&Token::LBrace)?;
 
+        self.function_depth += 1;
         let body = self.parse_block_statement();
+        self.function_depth -= 1;
 
         Ok(Expression::FnLiteral(FnLiteralExpression {
             name: "".to_string(),
@@ -349,8 +642,9 @@ impl Parser {
             }
             token => {
                 return Err(format!(
-                    "expected identifier, found {}",
-                    token.get_literal()
+                    "expected identifier, found {} ({})",
+                    token.get_literal(),
+                    self.curr_position
                 ))
             }
         }
@@ -364,8 +658,9 @@ impl Parser {
                 }),
                 token => {
                     return Err(format!(
-                        "expected identifier, found {}",
-                        token.get_literal()
+                        "expected identifier, found {} ({})",
+                        token.get_literal(),
+                        self.curr_position
                     ))
                 }
             }
@@ -383,9 +678,20 @@ impl Parser {
     }
 
     fn parse_call_expression(&mut self, lhs: Box<Expression>) -> Result<Expression, ParseError> {
+        let arguments = self
+            .parse_expression_list(Token::RParen)?
+            .into_iter()
+            .map(|arg| match arg {
+                Expression::Identifier(identifier) if identifier.name == "_" => {
+                    Expression::Placeholder
+                }
+                arg => arg,
+            })
+            .collect();
+
         Ok(Expression::Call(CallExpression {
             function: lhs,
-            arguments: self.parse_expression_list(Token::RParen)?,
+            arguments,
         }))
     }
 
@@ -404,16 +710,12 @@ impl Parser {
         }
 
         self.next_token();
-        if let Ok(expr) = self.parse_expression(LOWEST) {
-            exprs.push(expr);
-        }
+        exprs.push(self.parse_expression(LOWEST)?);
 
         while self.peek_token == Token::Comma {
             self.next_token();
             self.next_token();
-            if let Ok(expr) = self.parse_expression(LOWEST) {
-                exprs.push(expr);
-            }
+            exprs.push(self.parse_expression(LOWEST)?);
         }
 
         self.expect_peek(&end)?;
@@ -433,17 +735,42 @@ impl Parser {
     }
 
     fn parse_hash_literal_expression(&mut self) -> Result<Expression, ParseError> {
-        let mut pairs = BTreeMap::new();
-
-        while self.peek_token != Token::RBrace {
-            self.next_token();
-            let key = self.parse_expression(LOWEST)?;
+        self.parse_hash_literal_rest(None)
+    }
 
-            self.expect_peek(&Token::Colon)?;
+    // `first_key`, if any, has already been parsed as the current token.
+    fn parse_hash_literal_rest(
+        &mut self,
+        mut first_key: Option<Expression>,
+    ) -> Result<Expression, ParseError> {
+        let mut pairs = Vec::new();
+
+        while first_key.is_some() || self.peek_token != Token::RBrace {
+            let key = match first_key.take() {
+                Some(key) => key,
+                None => {
+                    self.next_token();
+                    self.parse_expression(LOWEST)?
+                }
+            };
+
+            // `{x, y}` is shorthand for `{"x": x, "y": y}`.
+            let shorthand = matches!(self.peek_token, Token::Comma | Token::RBrace);
+            match key {
+                Expression::Identifier(identifier) if shorthand => {
+                    let name = StringExpression {
+                        value: identifier.name.clone(),
+                    };
+                    pairs.push((Expression::String(name), Expression::Identifier(identifier)));
+                }
+                key => {
+                    self.expect_peek(&Token::Colon)?;
 
-            self.next_token();
-            let value = self.parse_expression(LOWEST)?;
-            pairs.insert(key, value);
+                    self.next_token();
+                    let value = self.parse_expression(LOWEST)?;
+                    pairs.push((key, value));
+                }
+            }
 
             if self.peek_token != Token::RBrace {
                 self.expect_peek(&Token::Comma)?;
@@ -457,7 +784,13 @@ impl Parser {
 
     fn next_token(&mut self) {
         self.curr_token = self.peek_token.clone();
+        self.curr_position = self.peek_position;
         self.peek_token = self.lexer.next_token();
+        self.peek_position = self.lexer.token_position();
+    }
+
+    fn peek_starts_statement(&self) -> bool {
+        self.semicolon_optional && self.peek_position.line > self.curr_position.line
     }
 
     fn expect_peek(&mut self, expected: &Token) -> Result<(), ParseError> {
@@ -466,7 +799,11 @@ impl Parser {
                 self.next_token();
                 Ok(())
             }
-            _ => Err(fmt_token_error(expected, &self.peek_token)),
+            _ => Err(fmt_token_error(
+                expected,
+                &self.peek_token,
+                &self.peek_position,
+            )),
         }
     }
 }
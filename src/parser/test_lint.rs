@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::{lint::lint_unused, parse_program};
+
+    fn test_linting(input: &str, expected: &[&str]) {
+        let program = parse_program(input).expect("error occurred while parsing program");
+        assert_eq!(lint_unused(&program), expected, "input: {}", input);
+    }
+
+    #[test]
+    fn test_lint_unused() {
+        let inputs = [
+            "let x = 5;",
+            "let x = 5; x;",
+            "let x = 5; let y = x; y;",
+            "let x = 1; let x = 2; x;",
+            "let x = 1; let x = x + 1; x;",
+            "let f = fn(a) { let b = a; 1 }; f(2);",
+            "let x = 1; let f = fn(x) { x }; f(2);",
+            "let x = 1; if (true) { let x = 2; x };",
+            "let x = 1; if (true) { let y = 2; x };",
+            "let fact = fn(n) { if (n < 2) { 1 } else { n * fact(n - 1) } }; fact(5);",
+            "let f = fn() { 1 };",
+            "const limit = 10;",
+            "try { let a = 1; 2 } catch (e) { 3 };",
+            "let n = 1; match 5 { case n if n > 2 => n };",
+            "let a = 1; let b = 2; do { let c = 3; a };",
+            "let n = 1; match 5 { case n => n };",
+            "match 5 { case n => 0 };",
+            "let f = fn() { g() }; let g = fn() { 1 }; f();",
+            "let even = fn(n) { if (n == 0) { true } else { odd(n - 1) } };
+            let odd = fn(n) { if (n == 0) { false } else { even(n - 1) } };
+            even(4);",
+            "let f = fn() { do { g() } }; let g = 1; f();",
+            "x; let x = 1;",
+        ];
+        let expected_messages: [&[&str]; 21] = [
+            &["unused variable: x"],
+            &[],
+            &[],
+            &["unused variable: x"],
+            &[],
+            &["unused variable: b"],
+            &["unused variable: x"],
+            &["unused variable: x"],
+            &["unused variable: y"],
+            &[],
+            &["unused variable: f"],
+            &[],
+            &["unused variable: a"],
+            &["unused variable: n"],
+            &["unused variable: b", "unused variable: c"],
+            &["unused variable: n"],
+            &[],
+            &[],
+            &[],
+            &[],
+            &["unused variable: x"],
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_linting(input, expected_messages[i]);
+        }
+    }
+}
@@ -1,16 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
-
     use crate::{
         lexer::token::Token,
         parser::ast::{
-            ArrayLiteralExpression, BlockStatement, BooleanExpression, CallExpression, Expression,
-            ExpressionStatement, FnLiteralExpression, HashLiteralExpression, IdentifierExpression,
-            IfExpression, IndexExpression, InfixExpression, IntegerExpression, LetStatement, Node,
-            PrefixExpression, Program, ReturnStatement, Statement, StringExpression,
+            ArrayLiteralExpression, BlockStatement, BooleanExpression, CallExpression,
+            DoExpression, Expression, ExpressionStatement, FnLiteralExpression,
+            HashLiteralExpression, IdentifierExpression, IfExpression, IndexExpression,
+            InfixExpression, IntegerExpression, LetStatement, MatchArm, MatchExpression, Node,
+            PrefixExpression, Program, ReturnStatement, Statement, StringExpression, TryExpression,
         },
-        parser::parse,
+        parser::{parse, parse_program, parse_program_semicolon_optional},
     };
 
     fn test_parsing(input: &str, expected: Vec<Statement>) {
@@ -23,6 +22,11 @@ mod tests {
         assert_eq!(parsed_input.to_string(), expected.to_string());
     }
 
+    fn test_parsing_errors(input: &str, expected: &[&str]) {
+        let errs = parse(input).expect_err("expected errors while parsing program");
+        assert_eq!(errs, expected);
+    }
+
     #[test]
     fn test_parse_let_statements() {
         let input = "let x = 5;
@@ -55,24 +59,60 @@ let foobar = y;";
 
     #[test]
     fn test_parse_return_statements() {
-        let input = "return 5;
+        let input = "fn() {
+return 5;
 return true;
-return x;";
+return x;
+}";
 
-        let expected = vec![
-            Statement::Return(ReturnStatement {
-                value: Expression::Integer(IntegerExpression { value: 5 }),
-            }),
-            Statement::Return(ReturnStatement {
-                value: Expression::Boolean(BooleanExpression { value: true }),
-            }),
-            Statement::Return(ReturnStatement {
-                value: Expression::Identifier(IdentifierExpression {
-                    name: "x".to_string(),
-                }),
+        let expected = vec![Statement::Expression(ExpressionStatement {
+            expr: Expression::FnLiteral(FnLiteralExpression {
+                name: "".to_string(),
+                parameters: vec![],
+                body: BlockStatement {
+                    statements: vec![
+                        Statement::Return(ReturnStatement {
+                            value: Expression::Integer(IntegerExpression { value: 5 }),
+                        }),
+                        Statement::Return(ReturnStatement {
+                            value: Expression::Boolean(BooleanExpression { value: true }),
+                        }),
+                        Statement::Return(ReturnStatement {
+                            value: Expression::Identifier(IdentifierExpression {
+                                name: "x".to_string(),
+                            }),
+                        }),
+                    ],
+                },
             }),
-        ];
+        })];
         test_parsing(input, expected);
+
+        test_parsing_to_string(
+            "fn() { if (x) { return 1; } do { return 2; } }",
+            "fn() { if (x) { return 1; };do { return 2; }; };",
+        );
+        test_parsing_to_string(
+            "fn() { fn() { return 1; } }",
+            "fn() { fn() { return 1; }; };",
+        );
+    }
+
+    #[test]
+    fn test_parse_return_outside_function() {
+        test_parsing_errors("return 5;", &["return outside of function (line 1, col 1)"]);
+        test_parsing_errors(
+            "if (x) { return 1; }",
+            &["return outside of function (line 1, col 10)"],
+        );
+        test_parsing_errors(
+            "{ let y = 1; return y; }",
+            &["return outside of function (line 1, col 14)"],
+        );
+        test_parsing_errors(
+            "let f = fn() { 1 }; return f();",
+            &["return outside of function (line 1, col 21)"],
+        );
     }
 
     #[test]
@@ -148,6 +188,23 @@ return x;";
         test_parsing(input, expected);
     }
 
+    #[test]
+    fn test_parse_prefix_minus_power_expression() {
+        let input = "-2 ** 2;";
+
+        let expected = vec![Statement::Expression(ExpressionStatement {
+            expr: Expression::Prefix(PrefixExpression {
+                operator: Token::Minus,
+                operand: Box::new(Expression::Infix(InfixExpression {
+                    operator: Token::Power,
+                    lhs: Box::new(Expression::Integer(IntegerExpression { value: 2 })),
+                    rhs: Box::new(Expression::Integer(IntegerExpression { value: 2 })),
+                })),
+            }),
+        })];
+        test_parsing(input, expected);
+    }
+
     #[test]
     fn test_parse_infix_expressions() {
         let inputs = ["5 + 6;", "5 - 6;", "5 == 6;", "5 != 6;"];
@@ -184,6 +241,20 @@ return x;";
             "add(a + b + c * d / f + g);",
             "a * [1, 2, 3, 4][b * c] * d",
             "add(a * b[2], b[1], 2 * [1, 2][1])",
+            "-2 ** 2;",
+            "2 ** 3 ** 2;",
+            "2 * 3 ** 2;",
+            "a ** b[1];",
+            "x |> f;",
+            "x |> f(y) |> g;",
+            "a + b |> f(c * d);",
+            "x |> f == y;",
+            "x |> f < y |> g;",
+            "x |> f(y, _, _);",
+            "1 < x < 10;",
+            "(1 < x) < 10;",
+            "a + 1 < b > c * 2 < d;",
+            "a < b < c == true;",
         ];
         let expected_strings = [
             "((-a) * b);",
@@ -202,6 +273,101 @@ return x;";
             "add((((a + b) + ((c * d) / f)) + g));",
             "((a * ([1, 2, 3, 4][(b * c)])) * d);",
             "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])));",
+            "(-(2 ** 2));",
+            "(2 ** (3 ** 2));",
+            "(2 * (3 ** 2));",
+            "(a ** (b[1]));",
+            "f(x);",
+            "g(f(x, y));",
+            "f((a + b), (c * d));",
+            "(f(x) == y);",
+            "(f(x) < g(y));",
+            "f(y, x, _);",
+            "(1 < x < 10);",
+            "((1 < x) < 10);",
+            "((a + 1) < b > (c * 2) < d);",
+            "((a < b < c) == true);",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_parsing_to_string(input, expected_strings[i]);
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence_levels() {
+        let integer = |value| Expression::Integer(IntegerExpression { value });
+        let boolean = |value| Expression::Boolean(BooleanExpression { value });
+        let infix = |lhs, operator, rhs| {
+            Expression::Infix(InfixExpression {
+                operator,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            })
+        };
+
+        let inputs = ["1 + 2 == 3", "2 * 3 > 4", "!true == false"];
+        let expected_exprs = [
+            infix(
+                infix(integer(1), Token::Plus, integer(2)),
+                Token::Equal,
+                integer(3),
+            ),
+            infix(
+                infix(integer(2), Token::Asterisk, integer(3)),
+                Token::GreaterThan,
+                integer(4),
+            ),
+            infix(
+                Expression::Prefix(PrefixExpression {
+                    operator: Token::Bang,
+                    operand: Box::new(boolean(true)),
+                }),
+                Token::Equal,
+                boolean(false),
+            ),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let expected = vec![Statement::Expression(ExpressionStatement {
+                expr: expected_exprs[i].clone(),
+            })];
+            test_parsing(input, expected);
+        }
+
+        // Each level binds tighter than the one before it: equality, comparison, sum, product,
+        // prefix, power, then calls and indexing.
+        let inputs = [
+            "a == b < c",
+            "a < b == c",
+            "a != b > c + d",
+            "a + b > c * d",
+            "a * b == c - d",
+            "a - b * c == d",
+            "-a ** b == c",
+            "!a == !b",
+            "a + f(b) * c[d] > e",
+            "(a == b) < c",
+            "a * (b + c) == d",
+            "a == b == c",
+            "a - b - c",
+            "a ** b ** c",
+        ];
+        let expected_strings = [
+            "(a == (b < c));",
+            "((a < b) == c);",
+            "(a != (b > (c + d)));",
+            "((a + b) > (c * d));",
+            "((a * b) == (c - d));",
+            "((a - (b * c)) == d);",
+            "((-(a ** b)) == c);",
+            "((!a) == (!b));",
+            "((a + (f(b) * (c[d]))) > e);",
+            "((a == b) < c);",
+            "((a * (b + c)) == d);",
+            "((a == b) == c);",
+            "((a - b) - c);",
+            "(a ** (b ** c));",
         ];
 
         for (i, input) in inputs.iter().enumerate() {
@@ -287,6 +453,100 @@ return x;";
         test_parsing(input, expected);
     }
 
+    #[test]
+    fn test_parse_do_expression() {
+        let input = "do { x; }";
+
+        let expected = vec![Statement::Expression(ExpressionStatement {
+            expr: Expression::Do(DoExpression {
+                body: BlockStatement {
+                    statements: vec![Statement::Expression(ExpressionStatement {
+                        expr: Expression::Identifier(IdentifierExpression {
+                            name: "x".to_string(),
+                        }),
+                    })],
+                },
+            }),
+        })];
+        test_parsing(input, expected);
+    }
+
+    #[test]
+    fn test_parse_try_expression() {
+        let input = "try { x; } catch (e) { e; }";
+
+        let expected = vec![Statement::Expression(ExpressionStatement {
+            expr: Expression::Try(TryExpression {
+                body: BlockStatement {
+                    statements: vec![Statement::Expression(ExpressionStatement {
+                        expr: Expression::Identifier(IdentifierExpression {
+                            name: "x".to_string(),
+                        }),
+                    })],
+                },
+                error: IdentifierExpression {
+                    name: "e".to_string(),
+                },
+                handler: BlockStatement {
+                    statements: vec![Statement::Expression(ExpressionStatement {
+                        expr: Expression::Identifier(IdentifierExpression {
+                            name: "e".to_string(),
+                        }),
+                    })],
+                },
+            }),
+        })];
+        test_parsing(input, expected);
+    }
+
+    #[test]
+    fn test_parse_match_expression() {
+        let input = "match x { case 1 => \"one\", default => y }";
+
+        let expected = vec![Statement::Expression(ExpressionStatement {
+            expr: Expression::Match(MatchExpression {
+                subject: Box::new(Expression::Identifier(IdentifierExpression {
+                    name: "x".to_string(),
+                })),
+                arms: vec![MatchArm {
+                    value: Expression::Integer(IntegerExpression { value: 1 }),
+                    guard: None,
+                    body: Expression::String(StringExpression {
+                        value: "one".to_string(),
+                    }),
+                }],
+                default: Some(Box::new(Expression::Identifier(IdentifierExpression {
+                    name: "y".to_string(),
+                }))),
+            }),
+        })];
+        test_parsing(input, expected);
+        test_parsing_to_string(
+            "match x + 1 { case 1 => a, case 2 => b * 2, }",
+            "match (x + 1) { case 1 => a, case 2 => (b * 2) };",
+        );
+        test_parsing_to_string(
+            "match x { case n if n > 10 => n, case 0 if flag => 0 }",
+            "match x { case n if (n > 10) => n, case 0 if flag => 0 };",
+        );
+        test_parsing_errors(
+            "match x { 1 => a }",
+            &[
+                "expected case or default, found 1 (line 1, col 11)",
+                "no prefix function found for => (line 1, col 13)",
+                "no prefix function found for } (line 1, col 18)",
+            ],
+        );
+        test_parsing_errors(
+            "match x { default => a, default => b }",
+            &[
+                "duplicate default arm (line 1, col 25)",
+                "no prefix function found for => (line 1, col 33)",
+                "no prefix function found for } (line 1, col 38)",
+            ],
+        );
+    }
+
     #[test]
     fn test_parse_function_literal() {
         let input = "fn(x, y) { x + y; }";
@@ -377,6 +637,25 @@ return x;";
         test_parsing(input, expected);
     }
 
+    #[test]
+    fn test_parse_call_expression_with_placeholders() {
+        let input = "sub(_, 1)";
+
+        let expected = vec![Statement::Expression(ExpressionStatement {
+            expr: Expression::Call(CallExpression {
+                function: Box::new(Expression::Identifier(IdentifierExpression {
+                    name: "sub".to_string(),
+                })),
+                arguments: vec![
+                    Expression::Placeholder,
+                    Expression::Integer(IntegerExpression { value: 1 }),
+                ],
+            }),
+        })];
+        test_parsing(input, expected);
+        test_parsing_to_string("add(_, f(_), _)", "add(_, f(_), _);");
+    }
+
     #[test]
     fn test_parse_array_literal() {
         let input = "[1, 2 * 2, 3 + 3]";
@@ -405,29 +684,30 @@ return x;";
     fn test_parse_hash_literal() {
         let input = "{ \"one\": 1, true: 2, 3: 16/4 }";
 
-        let mut expected_map = BTreeMap::new();
-        expected_map.insert(
-            Expression::String(StringExpression {
-                value: "one".to_string(),
-            }),
-            Expression::Integer(IntegerExpression { value: 1 }),
-        );
-        expected_map.insert(
-            Expression::Boolean(BooleanExpression { value: true }),
-            Expression::Integer(IntegerExpression { value: 2 }),
-        );
-        expected_map.insert(
-            Expression::Integer(IntegerExpression { value: 3 }),
-            Expression::Infix(InfixExpression {
-                operator: Token::Slash,
-                lhs: Box::new(Expression::Integer(IntegerExpression { value: 16 })),
-                rhs: Box::new(Expression::Integer(IntegerExpression { value: 4 })),
-            }),
-        );
+        let expected_pairs = vec![
+            (
+                Expression::String(StringExpression {
+                    value: "one".to_string(),
+                }),
+                Expression::Integer(IntegerExpression { value: 1 }),
+            ),
+            (
+                Expression::Boolean(BooleanExpression { value: true }),
+                Expression::Integer(IntegerExpression { value: 2 }),
+            ),
+            (
+                Expression::Integer(IntegerExpression { value: 3 }),
+                Expression::Infix(InfixExpression {
+                    operator: Token::Slash,
+                    lhs: Box::new(Expression::Integer(IntegerExpression { value: 16 })),
+                    rhs: Box::new(Expression::Integer(IntegerExpression { value: 4 })),
+                }),
+            ),
+        ];
 
         let expected = vec![Statement::Expression(ExpressionStatement {
             expr: Expression::HashLiteral(HashLiteralExpression {
-                pairs: expected_map,
+                pairs: expected_pairs,
             }),
         })];
         test_parsing(input, expected);
@@ -438,13 +718,34 @@ return x;";
         let input = "{ }";
 
         let expected = vec![Statement::Expression(ExpressionStatement {
-            expr: Expression::HashLiteral(HashLiteralExpression {
-                pairs: BTreeMap::new(),
-            }),
+            expr: Expression::HashLiteral(HashLiteralExpression { pairs: Vec::new() }),
         })];
         test_parsing(input, expected);
     }
 
+    #[test]
+    fn test_parse_hash_literal_shorthand() {
+        let inputs = ["{a, b}", "{a, \"c\": 3, b}", "{a: 1, b}"];
+        let expected_strings = [
+            "{\"a\": a, \"b\": b};",
+            "{\"a\": a, \"c\": 3, \"b\": b};",
+            "{a: 1, \"b\": b};",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_parsing_to_string(input, expected_strings[i]);
+        }
+
+        test_parsing_errors(
+            "{1, 2}",
+            &[
+                "expected :, found , (line 1, col 3)",
+                "no prefix function found for , (line 1, col 3)",
+                "no prefix function found for } (line 1, col 6)",
+            ],
+        );
+    }
+
     #[test]
     fn test_parse_index_expression() {
         let input = "myArray[1 + 1]";
@@ -463,4 +764,176 @@ return x;";
         })];
         test_parsing(input, expected);
     }
+
+    #[test]
+    fn test_parse_error_positions() {
+        let inputs = [
+            "let x 5;",
+            "let a = 1;\nlet b = 2;\nlet c = 3;\nlet = 4;",
+            "let f = fn(x) {\n    x + ;\n};",
+            "add(1, 2;",
+            "let a = @;\nlet b = 1 $ 2;",
+        ];
+        let expected_errors = [
+            vec!["expected =, found 5 (line 1, col 7)"],
+            vec![
+                "expected identifier, found = (line 4, col 5)",
+                "no prefix function found for = (line 4, col 5)",
+            ],
+            vec!["no prefix function found for ; (line 2, col 9)"],
+            vec!["expected ), found ; (line 1, col 9)"],
+            vec![
+                "illegal character: @ (line 1, col 9)",
+                "no prefix function found for ; (line 1, col 10)",
+                "illegal character: $ (line 2, col 11)",
+            ],
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_parsing_errors(input, &expected_errors[i]);
+        }
+    }
+
+    #[test]
+    fn test_parse_program() {
+        let program = parse_program("let x = 5; let y = x * 2; y;")
+            .expect("error occurred while parsing program");
+        assert_eq!(program.0.len(), 3);
+
+        let errs =
+            parse_program("let x 5; let = 2;").expect_err("expected errors while parsing program");
+        assert!(!errs.is_empty());
+    }
+
+    #[test]
+    fn test_parse_const_statements() {
+        let input = "const x = 5;";
+        let expected = vec![Statement::Const(LetStatement {
+            identifier: IdentifierExpression {
+                name: String::from("x"),
+            },
+            value: Expression::Integer(IntegerExpression { value: 5 }),
+        })];
+        test_parsing(input, expected);
+    }
+
+    #[test]
+    fn test_display_program_round_trip() {
+        let input = r#"
+            let add = fn(x, y) { x + y; };
+            const greeting = "hello";
+            let flag = !true;
+            if (flag) { add(1, 2) } else { [1, 2.5][0] };
+            let h = {"a": 1, 2: do { 3 }};
+            try { h["a"] } catch (e) { -1 };
+            match add(1, _) { case n if n > 2 => n, default => 0 };
+            let greet = fn() { return greeting; };
+        "#;
+        let program = parse_program(input).expect("error occurred while parsing program");
+        let displayed = program.to_string();
+        assert_eq!(
+            displayed,
+            [
+                "let add = fn(x, y) { (x + y); };",
+                "const greeting = \"hello\";",
+                "let flag = (!true);",
+                "if (flag) { add(1, 2); } else { ([1, 2.5][0]); };",
+                "let h = {\"a\": 1, 2: do { 3; }};",
+                "try { (h[\"a\"]); } catch (e) { (-1); };",
+                "match add(1, _) { case n if (n > 2) => n, default => 0 };",
+                "let greet = fn() { return greeting; };",
+            ]
+            .join("\n")
+        );
+
+        let reparsed = parse_program(&displayed).expect("error occurred while reparsing program");
+        assert_eq!(reparsed, program);
+    }
+
+    #[test]
+    fn test_parse_semicolon_optional() {
+        let input = "let x = 1
+            let f = fn(a) {
+                let b = a * 2
+                b + 1
+            }
+            f(x)
+            (x)
+            -x
+            [1, 2][0]
+            let y = x +
+                2
+            let g = fn() {
+                return y
+            }";
+        let with_semicolons = "let x = 1;
+            let f = fn(a) { let b = a * 2; b + 1; };
+            f(x);
+            (x);
+            -x;
+            [1, 2][0];
+            let y = x + 2;
+            let g = fn() { return y; };";
+
+        let program =
+            parse_program_semicolon_optional(input).expect("error occurred while parsing program");
+        let expected =
+            parse_program(with_semicolons).expect("error occurred while parsing program");
+        assert_eq!(program, expected);
+        assert_eq!(
+            parse_program_semicolon_optional(with_semicolons)
+                .expect("error occurred while parsing program"),
+            expected
+        );
+
+        assert_ne!(parse_program(input), Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_block_statements() {
+        let input = "{ let x = 1; x }";
+        let expected = vec![Statement::Block(BlockStatement {
+            statements: vec![
+                Statement::Let(LetStatement {
+                    identifier: IdentifierExpression {
+                        name: "x".to_string(),
+                    },
+                    value: Expression::Integer(IntegerExpression { value: 1 }),
+                }),
+                Statement::Expression(ExpressionStatement {
+                    expr: Expression::Identifier(IdentifierExpression {
+                        name: "x".to_string(),
+                    }),
+                }),
+            ],
+        })];
+        test_parsing(input, expected);
+
+        let inputs = [
+            "{ x }",
+            "{ x + 1; { y } }",
+            "{ f(x); let y = x; }",
+            "{}",
+            "{\"a\": 1}[\"a\"]",
+            "{a, b}",
+            "{[1]: 2}",
+            "if (x) { {\"a\": 1} }",
+        ];
+        let expected_strings = [
+            "{ x; }",
+            "{ (x + 1);{ y; } }",
+            "{ f(x);let y = x; }",
+            "{};",
+            "({\"a\": 1}[\"a\"]);",
+            "{\"a\": a, \"b\": b};",
+            "{[1]: 2};",
+            "if (x) { {\"a\": 1}; };",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_parsing_to_string(input, expected_strings[i]);
+        }
+
+        test_parsing_errors("{ let x = 1;", &["expected }, found Eof (line 1, col 13)"]);
+    }
 }
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        object::Integer,
+        parser::{
+            ast::{FnLiteralExpression, IntegerExpression},
+            parse, parse_program,
+            visitor::{walk, Visitor},
+        },
+    };
+
+    #[derive(Default)]
+    struct IntegerCounter {
+        count: usize,
+        sum: Integer,
+    }
+
+    impl Visitor for IntegerCounter {
+        fn visit_integer(&mut self, expr: &IntegerExpression) {
+            self.count += 1;
+            self.sum += expr.value;
+        }
+    }
+
+    #[test]
+    fn test_visitor_counts_integers() {
+        let inputs = [
+            "1 + 2 * 3",
+            "let x = 5; let f = fn(y) { y + 10 }; f(x);",
+            "if (1 > 2) { [3, 4][0] } else { {5: 6}[5] }",
+            "match x { case 1 if y > 2 => 3, default => do { 4 } }",
+            "try { add(_, 1) } catch (e) { -1 }",
+            "\"no integers\"; true; 1.5;",
+        ];
+        let expected_counts = [3, 2, 8, 4, 2, 0];
+        let expected_sums = [6, 15, 26, 10, 2, 0];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = parse(input).expect("error occurred while parsing program");
+            let mut counter = IntegerCounter::default();
+            walk(&mut counter, &program);
+            assert_eq!(counter.count, expected_counts[i], "input: {}", input);
+            assert_eq!(counter.sum, expected_sums[i], "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_visitor_overrides_can_skip_subtrees() {
+        struct TopLevelCounter(usize);
+
+        impl Visitor for TopLevelCounter {
+            fn visit_integer(&mut self, _expr: &IntegerExpression) {
+                self.0 += 1;
+            }
+
+            fn visit_fn_literal(&mut self, _expr: &FnLiteralExpression) {}
+        }
+
+        let program = parse_program("let f = fn() { 1 + 2 }; f() + 3;")
+            .expect("error occurred while parsing program");
+        let mut counter = TopLevelCounter(0);
+        counter.visit_program(&program);
+        assert_eq!(counter.0, 1);
+    }
+}
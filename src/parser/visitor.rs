@@ -0,0 +1,202 @@
+use super::ast::*;
+
+// Every `visit_*` method defaults to walking the node's children, so implementors only override
+// the nodes they care about and call the matching `walk_*` function to keep descending.
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        walk_statement(self, stmt);
+    }
+
+    fn visit_let_statement(&mut self, stmt: &LetStatement) {
+        walk_let_statement(self, stmt);
+    }
+
+    fn visit_const_statement(&mut self, stmt: &LetStatement) {
+        walk_let_statement(self, stmt);
+    }
+
+    fn visit_return_statement(&mut self, stmt: &ReturnStatement) {
+        self.visit_expression(&stmt.value);
+    }
+
+    fn visit_expression_statement(&mut self, stmt: &ExpressionStatement) {
+        self.visit_expression(&stmt.expr);
+    }
+
+    fn visit_block_statement(&mut self, block: &BlockStatement) {
+        walk_block_statement(self, block);
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        walk_expression(self, expr);
+    }
+
+    fn visit_identifier(&mut self, _expr: &IdentifierExpression) {}
+
+    fn visit_integer(&mut self, _expr: &IntegerExpression) {}
+
+    fn visit_float(&mut self, _expr: &FloatExpression) {}
+
+    fn visit_string(&mut self, _expr: &StringExpression) {}
+
+    fn visit_boolean(&mut self, _expr: &BooleanExpression) {}
+
+    fn visit_placeholder(&mut self) {}
+
+    fn visit_prefix(&mut self, expr: &PrefixExpression) {
+        self.visit_expression(&expr.operand);
+    }
+
+    fn visit_infix(&mut self, expr: &InfixExpression) {
+        self.visit_expression(&expr.lhs);
+        self.visit_expression(&expr.rhs);
+    }
+
+    fn visit_comparison(&mut self, expr: &ComparisonExpression) {
+        for operand in expr.operands.iter() {
+            self.visit_expression(operand);
+        }
+    }
+
+    fn visit_if(&mut self, expr: &IfExpression) {
+        walk_if(self, expr);
+    }
+
+    fn visit_do(&mut self, expr: &DoExpression) {
+        self.visit_block_statement(&expr.body);
+    }
+
+    fn visit_try(&mut self, expr: &TryExpression) {
+        walk_try(self, expr);
+    }
+
+    fn visit_match(&mut self, expr: &MatchExpression) {
+        walk_match(self, expr);
+    }
+
+    fn visit_fn_literal(&mut self, expr: &FnLiteralExpression) {
+        walk_fn_literal(self, expr);
+    }
+
+    fn visit_array_literal(&mut self, expr: &ArrayLiteralExpression) {
+        for element in expr.elements.iter() {
+            self.visit_expression(element);
+        }
+    }
+
+    fn visit_hash_literal(&mut self, expr: &HashLiteralExpression) {
+        for (key, value) in expr.pairs.iter() {
+            self.visit_expression(key);
+            self.visit_expression(value);
+        }
+    }
+
+    fn visit_call(&mut self, expr: &CallExpression) {
+        self.visit_expression(&expr.function);
+        for arg in expr.arguments.iter() {
+            self.visit_expression(arg);
+        }
+    }
+
+    fn visit_index(&mut self, expr: &IndexExpression) {
+        self.visit_expression(&expr.identifier);
+        self.visit_expression(&expr.index);
+    }
+}
+
+pub fn walk<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    match node {
+        Node::Program(program) => visitor.visit_program(program),
+        Node::Statement(stmt) => visitor.visit_statement(stmt),
+        Node::Expression(expr) => visitor.visit_expression(expr),
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for stmt in program.0.iter() {
+        visitor.visit_statement(stmt);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Statement) {
+    match stmt {
+        Statement::Let(stmt) => visitor.visit_let_statement(stmt),
+        Statement::Const(stmt) => visitor.visit_const_statement(stmt),
+        Statement::Return(stmt) => visitor.visit_return_statement(stmt),
+        Statement::Expression(stmt) => visitor.visit_expression_statement(stmt),
+        Statement::Block(block) => visitor.visit_block_statement(block),
+    }
+}
+
+pub fn walk_let_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &LetStatement) {
+    visitor.visit_identifier(&stmt.identifier);
+    visitor.visit_expression(&stmt.value);
+}
+
+pub fn walk_block_statement<V: Visitor + ?Sized>(visitor: &mut V, block: &BlockStatement) {
+    for stmt in block.statements.iter() {
+        visitor.visit_statement(stmt);
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expression) {
+    match expr {
+        Expression::Identifier(expr) => visitor.visit_identifier(expr),
+        Expression::Integer(expr) => visitor.visit_integer(expr),
+        Expression::Float(expr) => visitor.visit_float(expr),
+        Expression::String(expr) => visitor.visit_string(expr),
+        Expression::Prefix(expr) => visitor.visit_prefix(expr),
+        Expression::Infix(expr) => visitor.visit_infix(expr),
+        Expression::Comparison(expr) => visitor.visit_comparison(expr),
+        Expression::Boolean(expr) => visitor.visit_boolean(expr),
+        Expression::If(expr) => visitor.visit_if(expr),
+        Expression::Do(expr) => visitor.visit_do(expr),
+        Expression::Try(expr) => visitor.visit_try(expr),
+        Expression::Match(expr) => visitor.visit_match(expr),
+        Expression::FnLiteral(expr) => visitor.visit_fn_literal(expr),
+        Expression::ArrayLiteral(expr) => visitor.visit_array_literal(expr),
+        Expression::HashLiteral(expr) => visitor.visit_hash_literal(expr),
+        Expression::Call(expr) => visitor.visit_call(expr),
+        Expression::Index(expr) => visitor.visit_index(expr),
+        Expression::Placeholder => visitor.visit_placeholder(),
+    }
+}
+
+pub fn walk_if<V: Visitor + ?Sized>(visitor: &mut V, expr: &IfExpression) {
+    visitor.visit_expression(&expr.condition);
+    visitor.visit_block_statement(&expr.consequence);
+    if let Some(alternative) = &expr.alternative {
+        visitor.visit_block_statement(alternative);
+    }
+}
+
+pub fn walk_try<V: Visitor + ?Sized>(visitor: &mut V, expr: &TryExpression) {
+    visitor.visit_block_statement(&expr.body);
+    visitor.visit_identifier(&expr.error);
+    visitor.visit_block_statement(&expr.handler);
+}
+
+pub fn walk_match<V: Visitor + ?Sized>(visitor: &mut V, expr: &MatchExpression) {
+    visitor.visit_expression(&expr.subject);
+    for arm in expr.arms.iter() {
+        visitor.visit_expression(&arm.value);
+        if let Some(guard) = &arm.guard {
+            visitor.visit_expression(guard);
+        }
+        visitor.visit_expression(&arm.body);
+    }
+    if let Some(default) = &expr.default {
+        visitor.visit_expression(default);
+    }
+}
+
+pub fn walk_fn_literal<V: Visitor + ?Sized>(visitor: &mut V, expr: &FnLiteralExpression) {
+    for parameter in expr.parameters.iter() {
+        visitor.visit_identifier(parameter);
+    }
+    visitor.visit_block_statement(&expr.body);
+}
@@ -0,0 +1,265 @@
+use std::{
+    io::{self, IsTerminal, Read, Write},
+    mem,
+    process::{Command, Stdio},
+};
+
+use super::History;
+
+// Keys the line editor reacts to, decoded from raw terminal input.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Enter,
+    Tab,
+    Up,
+    Down,
+    Interrupt,
+    Eof,
+    Other,
+}
+
+// Reads one key press, or `None` at the end of the input.
+pub fn read_key(input: &mut impl Read) -> io::Result<Option<Key>> {
+    let Some(byte) = read_byte(input)? else {
+        return Ok(None);
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x03 => Key::Interrupt,
+        0x04 => Key::Eof,
+        0x1b => read_escape_sequence(input)?,
+        byte if byte < 0x20 => Key::Other,
+        byte => read_char(input, byte)?,
+    };
+    Ok(Some(key))
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    match input.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+// Sequences look like `ESC [ params final`, where the final byte is in 0x40..=0x7e.
+fn read_escape_sequence(input: &mut impl Read) -> io::Result<Key> {
+    if !matches!(read_byte(input)?, Some(b'[' | b'O')) {
+        return Ok(Key::Other);
+    }
+    while let Some(byte) = read_byte(input)? {
+        match byte {
+            b'A' => return Ok(Key::Up),
+            b'B' => return Ok(Key::Down),
+            0x40..=0x7e => break,
+            _ => {}
+        }
+    }
+    Ok(Key::Other)
+}
+
+fn read_char(input: &mut impl Read, first: u8) -> io::Result<Key> {
+    let len = match first.leading_ones() {
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        _ => 1,
+    };
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => break,
+        }
+    }
+
+    Ok(match String::from_utf8(bytes) {
+        Ok(string) => string.chars().next().map_or(Key::Other, Key::Char),
+        Err(_) => Key::Other,
+    })
+}
+
+// What the REPL should do after a key press.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Edit {
+    Redraw,
+    // More than one name completes the word before the cursor.
+    Candidates(Vec<String>),
+    Submit(String),
+    Eof,
+}
+
+// The line being typed at the prompt. Editing only happens at the end of the line.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct LineEditor {
+    buffer: String,
+    // The unsubmitted line, kept while stepping through the history.
+    draft: Option<String>,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    pub fn handle(
+        &mut self,
+        key: Key,
+        history: &mut History,
+        complete: impl Fn(&str) -> Vec<String>,
+    ) -> Edit {
+        match key {
+            Key::Char(c) => self.buffer.push(c),
+            Key::Backspace => {
+                self.buffer.pop();
+            }
+            Key::Enter => {
+                self.draft = None;
+                return Edit::Submit(mem::take(&mut self.buffer));
+            }
+            Key::Tab => return self.complete_word(complete),
+            Key::Up => {
+                if let Some(entry) = history.older() {
+                    let line = mem::replace(&mut self.buffer, entry.to_string());
+                    self.draft.get_or_insert(line);
+                }
+            }
+            Key::Down => match history.newer() {
+                Some(entry) => self.buffer = entry.to_string(),
+                None => {
+                    if let Some(draft) = self.draft.take() {
+                        self.buffer = draft;
+                    }
+                }
+            },
+            Key::Interrupt => self.buffer.clear(),
+            Key::Eof if self.buffer.is_empty() => return Edit::Eof,
+            Key::Eof | Key::Other => {}
+        }
+        Edit::Redraw
+    }
+
+    // Extends the identifier at the end of the line as far as all candidates agree.
+    fn complete_word(&mut self, complete: impl Fn(&str) -> Vec<String>) -> Edit {
+        let start = self
+            .buffer
+            .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_')
+            .len();
+        let word = &self.buffer[start..];
+        if word.is_empty() {
+            return Edit::Redraw;
+        }
+
+        let candidates = complete(word);
+        let Some(first) = candidates.first() else {
+            return Edit::Redraw;
+        };
+        let common = candidates.iter().fold(first.as_str(), |common, candidate| {
+            let len = common
+                .char_indices()
+                .zip(candidate.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((i, a), _)| i + a.len_utf8());
+            &common[..len]
+        });
+
+        if common.len() > word.len() {
+            self.buffer.replace_range(start.., common);
+            Edit::Redraw
+        } else if candidates.len() > 1 {
+            Edit::Candidates(candidates)
+        } else {
+            Edit::Redraw
+        }
+    }
+}
+
+// Prompts for a line, with tab completion and history recall when stdin is a terminal. Returns
+// `None` at the end of the input.
+pub fn read_line(
+    prompt: &str,
+    history: &mut History,
+    complete: impl Fn(&str) -> Vec<String>,
+) -> io::Result<Option<String>> {
+    let mut stdout = io::stdout();
+    write!(stdout, "{}", prompt)?;
+    stdout.flush()?;
+
+    let stdin = io::stdin();
+    let raw_mode = match stdin.is_terminal() {
+        true => RawMode::enable().ok(),
+        false => None,
+    };
+    if raw_mode.is_none() {
+        let mut line = String::new();
+        return match stdin.read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        };
+    }
+
+    let mut input = stdin.lock();
+    let mut editor = LineEditor::new();
+    loop {
+        let Some(key) = read_key(&mut input)? else {
+            return Ok(None);
+        };
+        match editor.handle(key, history, &complete) {
+            Edit::Redraw => {}
+            Edit::Candidates(candidates) => write!(stdout, "\r\n{}\r\n", candidates.join("  "))?,
+            Edit::Submit(line) => {
+                write!(stdout, "\r\n")?;
+                return Ok(Some(line));
+            }
+            Edit::Eof => {
+                write!(stdout, "\r\n")?;
+                return Ok(None);
+            }
+        }
+        write!(stdout, "\r\x1b[K{}{}", prompt, editor.buffer())?;
+        stdout.flush()?;
+    }
+}
+
+// Turns off line buffering, echo and signal keys until dropped. std has no portable way to do
+// this, so it goes through `stty`.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?.trim().to_string();
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
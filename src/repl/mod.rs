@@ -0,0 +1,76 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+pub mod editor;
+mod test_editor;
+mod test_repl;
+
+// Previously entered REPL lines, oldest first, with a cursor for stepping through them the way
+// the up and down arrows do.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct History {
+    entries: Vec<String>,
+    cursor: usize,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Loads the lines stored at `path`, if any, and appends new entries to it from then on.
+    pub fn with_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => contents.lines().map(String::from).collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err),
+        };
+
+        Ok(Self {
+            cursor: entries.len(),
+            entries,
+            path: Some(path),
+        })
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    // Blank lines are not recorded. Recording a line resets the cursor past the newest entry.
+    pub fn push(&mut self, line: &str) -> io::Result<()> {
+        let line = line.trim_end_matches(['\n', '\r']);
+        if !line.trim().is_empty() {
+            self.entries.push(line.to_string());
+            if let Some(path) = &self.path {
+                let mut file = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?;
+                writeln!(file, "{}", line)?;
+            }
+        }
+        self.cursor = self.entries.len();
+        Ok(())
+    }
+
+    pub fn older(&mut self) -> Option<&str> {
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        self.entries.get(self.cursor).map(String::as_str)
+    }
+
+    pub fn newer(&mut self) -> Option<&str> {
+        if self.cursor < self.entries.len() {
+            self.cursor += 1;
+        }
+        self.entries.get(self.cursor).map(String::as_str)
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use crate::repl::{
+        editor::{read_key, Edit, Key, LineEditor},
+        History,
+    };
+
+    fn complete(prefix: &str) -> Vec<String> {
+        ["len", "length", "let_me", "puts"]
+            .iter()
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_read_key() {
+        let mut input = "a\u{e9}\t\x7f\r\x03\x04\x1b[A\x1bOB\x1b[3~\x01".as_bytes();
+        let expected = [
+            Key::Char('a'),
+            Key::Char('\u{e9}'),
+            Key::Tab,
+            Key::Backspace,
+            Key::Enter,
+            Key::Interrupt,
+            Key::Eof,
+            Key::Up,
+            Key::Down,
+            Key::Other,
+            Key::Other,
+        ];
+
+        for key in expected {
+            assert_eq!(
+                read_key(&mut input).expect("error occurred while reading key"),
+                Some(key)
+            );
+        }
+        assert_eq!(
+            read_key(&mut input).expect("error occurred while reading key"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_line_editing() {
+        let mut history = History::new();
+        let mut editor = LineEditor::new();
+        for c in "lett".chars() {
+            assert_eq!(
+                editor.handle(Key::Char(c), &mut history, complete),
+                Edit::Redraw
+            );
+        }
+        editor.handle(Key::Backspace, &mut history, complete);
+        assert_eq!(editor.buffer(), "let");
+
+        assert_eq!(
+            editor.handle(Key::Eof, &mut history, complete),
+            Edit::Redraw
+        );
+        assert_eq!(
+            editor.handle(Key::Enter, &mut history, complete),
+            Edit::Submit("let".to_string())
+        );
+        assert_eq!(editor.buffer(), "");
+        assert_eq!(editor.handle(Key::Eof, &mut history, complete), Edit::Eof);
+
+        editor.handle(Key::Char('x'), &mut history, complete);
+        editor.handle(Key::Interrupt, &mut history, complete);
+        assert_eq!(editor.buffer(), "");
+    }
+
+    #[test]
+    fn test_tab_completion() {
+        let mut history = History::new();
+        let mut editor = LineEditor::new();
+        for c in "1 + pu".chars() {
+            editor.handle(Key::Char(c), &mut history, complete);
+        }
+        assert_eq!(
+            editor.handle(Key::Tab, &mut history, complete),
+            Edit::Redraw
+        );
+        assert_eq!(editor.buffer(), "1 + puts");
+
+        let mut editor = LineEditor::new();
+        editor.handle(Key::Char('l'), &mut history, complete);
+        assert_eq!(
+            editor.handle(Key::Tab, &mut history, complete),
+            Edit::Redraw
+        );
+        assert_eq!(editor.buffer(), "le");
+        assert_eq!(
+            editor.handle(Key::Tab, &mut history, complete),
+            Edit::Candidates(vec![
+                "len".to_string(),
+                "length".to_string(),
+                "let_me".to_string()
+            ])
+        );
+        editor.handle(Key::Char('n'), &mut history, complete);
+        assert_eq!(
+            editor.handle(Key::Tab, &mut history, complete),
+            Edit::Candidates(vec!["len".to_string(), "length".to_string()])
+        );
+
+        let mut editor = LineEditor::new();
+        for c in "zz ".chars() {
+            editor.handle(Key::Char(c), &mut history, complete);
+        }
+        assert_eq!(
+            editor.handle(Key::Tab, &mut history, complete),
+            Edit::Redraw
+        );
+        assert_eq!(editor.buffer(), "zz ");
+    }
+
+    #[test]
+    fn test_history_recall() {
+        let mut history = History::new();
+        history.push("let a = 1;").expect("history error");
+        history.push("a + 1").expect("history error");
+
+        let mut editor = LineEditor::new();
+        for c in "dra".chars() {
+            editor.handle(Key::Char(c), &mut history, complete);
+        }
+        editor.handle(Key::Up, &mut history, complete);
+        assert_eq!(editor.buffer(), "a + 1");
+        editor.handle(Key::Up, &mut history, complete);
+        assert_eq!(editor.buffer(), "let a = 1;");
+        editor.handle(Key::Up, &mut history, complete);
+        assert_eq!(editor.buffer(), "let a = 1;");
+        editor.handle(Key::Down, &mut history, complete);
+        assert_eq!(editor.buffer(), "a + 1");
+        editor.handle(Key::Down, &mut history, complete);
+        assert_eq!(editor.buffer(), "dra");
+        editor.handle(Key::Down, &mut history, complete);
+        assert_eq!(editor.buffer(), "dra");
+
+        editor.handle(Key::Up, &mut history, complete);
+        editor.handle(Key::Char(';'), &mut history, complete);
+        assert_eq!(
+            editor.handle(Key::Enter, &mut history, complete),
+            Edit::Submit("a + 1;".to_string())
+        );
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crate::repl::History;
+
+    #[test]
+    fn test_history_records_lines_in_order() {
+        let mut history = History::new();
+        for line in ["let x = 1;\n", "   \n", "x + 1\n", "puts(x)"] {
+            history
+                .push(line)
+                .expect("error occurred while recording line");
+        }
+
+        assert_eq!(history.entries(), ["let x = 1;", "x + 1", "puts(x)"]);
+    }
+
+    #[test]
+    fn test_history_navigation() {
+        let mut history = History::new();
+        assert_eq!(history.older(), None);
+
+        for line in ["a", "b", "c"] {
+            history
+                .push(line)
+                .expect("error occurred while recording line");
+        }
+
+        assert_eq!(history.older(), Some("c"));
+        assert_eq!(history.older(), Some("b"));
+        assert_eq!(history.older(), Some("a"));
+        assert_eq!(history.older(), None);
+        assert_eq!(history.newer(), Some("b"));
+        assert_eq!(history.newer(), Some("c"));
+        assert_eq!(history.newer(), None);
+        assert_eq!(history.newer(), None);
+
+        history.older();
+        history
+            .push("d")
+            .expect("error occurred while recording line");
+        assert_eq!(history.older(), Some("d"));
+    }
+
+    #[test]
+    fn test_history_persistence() {
+        let path = env::temp_dir().join(format!("monkey_history_{}", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut history = History::with_file(&path).expect("error occurred while loading history");
+        assert!(history.entries().is_empty());
+        history.push("let x = 1;").unwrap();
+        history.push("x").unwrap();
+
+        let mut reloaded = History::with_file(&path).expect("error occurred while loading history");
+        assert_eq!(reloaded.entries(), ["let x = 1;", "x"]);
+        assert_eq!(reloaded.older(), Some("x"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -28,3 +28,9 @@ impl Frame {
         self.closure.function.instructions.clone()
     }
 }
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
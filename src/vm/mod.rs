@@ -1,11 +1,13 @@
-use std::{collections::BTreeMap, rc::Rc};
+use std::{cell::RefCell, mem, rc::Rc};
 
 use crate::{
-    code::Opcode,
+    code::{Instructions, Opcode},
     compiler::Bytecode,
+    evaluator::{apply_callback, EvalError},
     object::{
-        builtins::{BuiltinFn, BUILTINS},
-        Closure, CompiledFn, HashKey, HashPair, Object,
+        arrays_equal,
+        builtins::{Builtin, BUILTINS},
+        integer_pow, range_get, Closure, CompiledFn, HashPair, HashPairs, Integer, Object,
     },
 };
 
@@ -20,6 +22,59 @@ const STACK_SIZE: usize = 2048;
 pub const GLOBALS_SIZE: usize = 65535;
 const MAX_FRAMES: usize = 1024;
 
+// A program's constants and globals.
+type ProgramState = (Vec<Rc<Object>>, Vec<Rc<Object>>);
+
+thread_local! {
+    // The state of a VM that is waiting on a builtin, lent to the nested VMs that run the
+    // closures the builtin calls back.
+    static LENT_STATE: RefCell<Option<ProgramState>> = const { RefCell::new(None) };
+}
+
+// Calls a compiled closure from a builtin, on a nested VM that sees the calling program's
+// constants and globals. An `exit` inside it is returned as the exit object.
+pub(crate) fn run_closure(closure: &Closure, args: &[Rc<Object>]) -> Result<Rc<Object>, VmError> {
+    let Some((constants, globals)) = LENT_STATE.with(|state| state.borrow_mut().take()) else {
+        return Err("compiled functions can only be called from the VM".to_string());
+    };
+
+    // The closure returns into an empty main frame, which ends the run.
+    let main_closure = Closure {
+        function: Rc::new(CompiledFn {
+            instructions: Instructions::new(),
+            num_locals: 0,
+            num_parameters: 0,
+        }),
+        free_vars: Vec::new(),
+    };
+    let mut frames = vec![Frame::new(); MAX_FRAMES];
+    frames[0] = Frame::from_function(main_closure, 0);
+
+    let mut vm = Vm {
+        constants,
+        stack: {
+            let rc = Rc::new(Object::Null);
+            vec![rc; STACK_SIZE]
+        },
+        sp: 0,
+        globals,
+        frames,
+        frames_idx: 1,
+        handlers: Vec::new(),
+    };
+    let result = vm.call_back(closure, args);
+
+    LENT_STATE.with(|state| *state.borrow_mut() = Some((vm.constants, vm.globals)));
+    result
+}
+
+// Where to resume when an error is raised inside a `try` body.
+struct Handler {
+    frames_idx: usize,
+    sp: usize,
+    ip: usize,
+}
+
 pub struct Vm {
     constants: Vec<Rc<Object>>,
     stack: Vec<Rc<Object>>,
@@ -28,6 +83,7 @@ pub struct Vm {
 
     frames: Vec<Frame>,
     frames_idx: usize,
+    handlers: Vec<Handler>,
 }
 
 impl Vm {
@@ -48,6 +104,7 @@ impl Vm {
 
             frames: Vec::new(),
             frames_idx: 1,
+            handlers: Vec::new(),
         }
     }
 
@@ -82,6 +139,7 @@ impl Vm {
 
             frames,
             frames_idx: 1,
+            handlers: Vec::new(),
         }
     }
 
@@ -104,11 +162,30 @@ impl Vm {
 
         self.frames = frames;
         self.frames_idx = 1;
+        self.handlers.clear();
     }
 
     pub fn run(&mut self) -> Result<(), VmError> {
-        while self.current_frame().ip
-            < (self.current_frame().instructions().stream.len() - 1) as i32
+        loop {
+            match self.execute() {
+                Err(err) => match self.handlers.pop() {
+                    Some(handler) => {
+                        self.frames_idx = handler.frames_idx;
+                        self.sp = handler.sp;
+                        self.current_frame_mut().ip = (handler.ip - 1) as i32;
+                        self.push_stack(Rc::new(Object::String(err)))?;
+                    }
+                    None => return Err(err),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    fn execute(&mut self) -> Result<(), VmError> {
+        // `ip` starts at -1, so compare against the next position to handle empty programs.
+        while ((self.current_frame().ip + 1) as usize)
+            < self.current_frame().instructions().stream.len()
         {
             self.current_frame_mut().ip += 1;
 
@@ -133,7 +210,7 @@ impl Vm {
                 Opcode::OpPop => {
                     self.pop_stack();
                 }
-                Opcode::OpAdd | Opcode::OpSub | Opcode::OpMul | Opcode::OpDiv => {
+                Opcode::OpAdd | Opcode::OpSub | Opcode::OpMul | Opcode::OpDiv | Opcode::OpPow => {
                     self.exec_binary_operation(&op)?;
                 }
                 Opcode::OpTrue => {
@@ -235,18 +312,27 @@ impl Vm {
                     self.current_frame_mut().ip += 1;
 
                     self.execute_call(num_args)?;
+
+                    // A builtin signalled program termination: halt, leaving
+                    // the exit object as the last popped value.
+                    if let Object::Exit(_) = self.stack[self.sp - 1].as_ref() {
+                        self.pop_stack();
+                        return Ok(());
+                    }
                 }
                 Opcode::OpReturnValue => {
                     let return_value = self.pop_stack();
 
                     let frame = self.pop_frame();
                     self.sp = frame.base_pointer - 1;
+                    self.drop_returned_handlers();
 
                     self.push_stack(return_value)?;
                 }
                 Opcode::OpReturn => {
                     let frame = self.pop_frame();
                     self.sp = frame.base_pointer - 1;
+                    self.drop_returned_handlers();
 
                     self.push_stack(Rc::new(Object::Null))?;
                 }
@@ -268,7 +354,7 @@ impl Vm {
                     let builtin_index = instr[ip + 1] as usize;
                     self.current_frame_mut().ip += 1;
 
-                    let definition = BUILTINS[builtin_index].1;
+                    let definition = &BUILTINS[builtin_index];
 
                     self.push_stack(Rc::new(Object::BuiltinFn(definition)))?;
                 }
@@ -295,6 +381,24 @@ impl Vm {
                     let current_closure = self.current_frame().closure.clone();
                     self.push_stack(Rc::new(Object::Closure(current_closure)))?;
                 }
+                Opcode::OpTry => match instr[ip + 1..ip + 3].try_into() {
+                    Ok(bytes) => {
+                        let handler_pos = u16::from_be_bytes(bytes) as usize;
+                        self.current_frame_mut().ip += 2;
+
+                        self.handlers.push(Handler {
+                            frames_idx: self.frames_idx,
+                            sp: self.sp,
+                            ip: handler_pos,
+                        });
+                    }
+                    Err(..) => {
+                        return Err("error in instruction".to_string());
+                    }
+                },
+                Opcode::OpEndTry => {
+                    self.handlers.pop();
+                }
             }
         }
         Ok(())
@@ -307,6 +411,15 @@ impl Vm {
             (Object::Integer(lhs_value), Object::Integer(rhs_value)) => {
                 self.exec_integer_binary_operation(op, *lhs_value, *rhs_value)
             }
+            (Object::Float(lhs_value), Object::Float(rhs_value)) => {
+                self.exec_float_binary_operation(op, *lhs_value, *rhs_value)
+            }
+            (Object::Integer(lhs_value), Object::Float(rhs_value)) => {
+                self.exec_float_binary_operation(op, *lhs_value as f64, *rhs_value)
+            }
+            (Object::Float(lhs_value), Object::Integer(rhs_value)) => {
+                self.exec_float_binary_operation(op, *lhs_value, *rhs_value as f64)
+            }
             (Object::String(lhs_value), Object::String(rhs_value)) => {
                 self.exec_string_binary_operation(op, lhs_value, rhs_value)
             }
@@ -321,14 +434,16 @@ impl Vm {
     fn exec_integer_binary_operation(
         &mut self,
         op: &Opcode,
-        lhs: i32,
-        rhs: i32,
+        lhs: Integer,
+        rhs: Integer,
     ) -> Result<(), VmError> {
         let result = match op {
             Opcode::OpAdd => Some(lhs + rhs),
             Opcode::OpSub => Some(lhs - rhs),
             Opcode::OpMul => Some(lhs * rhs),
+            Opcode::OpDiv if rhs == 0 => return Err("division by zero".to_string()),
             Opcode::OpDiv => Some(lhs / rhs),
+            Opcode::OpPow => Some(integer_pow(lhs, rhs)?),
             _ => None,
         };
 
@@ -338,6 +453,27 @@ impl Vm {
         }
     }
 
+    fn exec_float_binary_operation(
+        &mut self,
+        op: &Opcode,
+        lhs: f64,
+        rhs: f64,
+    ) -> Result<(), VmError> {
+        let result = match op {
+            Opcode::OpAdd => Some(lhs + rhs),
+            Opcode::OpSub => Some(lhs - rhs),
+            Opcode::OpMul => Some(lhs * rhs),
+            Opcode::OpDiv => Some(lhs / rhs),
+            Opcode::OpPow => Some(lhs.powf(rhs)),
+            _ => None,
+        };
+
+        match result {
+            Some(r) => self.push_stack(Rc::new(Object::Float(r))),
+            None => Err(format!("unknown FLOAT operator: {:?}", op)),
+        }
+    }
+
     fn exec_string_binary_operation(
         &mut self,
         op: &Opcode,
@@ -362,9 +498,21 @@ impl Vm {
             (Object::Integer(lhs_value), Object::Integer(rhs_value)) => {
                 self.exec_integer_comparison(op, *lhs_value, *rhs_value)
             }
+            (Object::Float(lhs_value), Object::Float(rhs_value)) => {
+                self.exec_float_comparison(op, *lhs_value, *rhs_value)
+            }
+            (Object::Integer(lhs_value), Object::Float(rhs_value)) => {
+                self.exec_float_comparison(op, *lhs_value as f64, *rhs_value)
+            }
+            (Object::Float(lhs_value), Object::Integer(rhs_value)) => {
+                self.exec_float_comparison(op, *lhs_value, *rhs_value as f64)
+            }
             (Object::Boolean(lhs_value), Object::Boolean(rhs_value)) => {
                 self.exec_boolean_comparison(op, *lhs_value, *rhs_value)
             }
+            (Object::Array(lhs_elements), Object::Array(rhs_elements)) => {
+                self.exec_array_comparison(op, lhs_elements, rhs_elements)
+            }
             _ => Err(format!(
                 "unsupported types for comparison: {} {}",
                 lhs.get_type_str(),
@@ -373,7 +521,30 @@ impl Vm {
         }
     }
 
-    fn exec_integer_comparison(&mut self, op: &Opcode, lhs: i32, rhs: i32) -> Result<(), VmError> {
+    fn exec_array_comparison(
+        &mut self,
+        op: &Opcode,
+        lhs: &[Rc<Object>],
+        rhs: &[Rc<Object>],
+    ) -> Result<(), VmError> {
+        let result = match op {
+            Opcode::OpEqual => Some(arrays_equal(lhs, rhs)),
+            Opcode::OpNotEqual => Some(!arrays_equal(lhs, rhs)),
+            _ => None,
+        };
+
+        match result {
+            Some(r) => self.push_stack(Rc::new(Object::Boolean(r))),
+            None => Err(format!("unknown ARRAY operator: {:?}", op)),
+        }
+    }
+
+    fn exec_integer_comparison(
+        &mut self,
+        op: &Opcode,
+        lhs: Integer,
+        rhs: Integer,
+    ) -> Result<(), VmError> {
         let result = match op {
             Opcode::OpEqual => Some(lhs == rhs),
             Opcode::OpNotEqual => Some(lhs != rhs),
@@ -387,6 +558,20 @@ impl Vm {
         }
     }
 
+    fn exec_float_comparison(&mut self, op: &Opcode, lhs: f64, rhs: f64) -> Result<(), VmError> {
+        let result = match op {
+            Opcode::OpEqual => Some(lhs == rhs),
+            Opcode::OpNotEqual => Some(lhs != rhs),
+            Opcode::OpGreaterThan => Some(lhs > rhs),
+            _ => None,
+        };
+
+        match result {
+            Some(r) => self.push_stack(Rc::new(Object::Boolean(r))),
+            None => Err(format!("unknown FLOAT operator: {:?}", op)),
+        }
+    }
+
     fn exec_boolean_comparison(
         &mut self,
         op: &Opcode,
@@ -408,7 +593,11 @@ impl Vm {
     fn exec_minus_operator(&mut self) -> Result<(), VmError> {
         let operand = self.pop_stack();
         match operand.as_ref() {
-            Object::Integer(value) => self.push_stack(Rc::new(Object::Integer(-value))),
+            Object::Integer(value) => match value.checked_neg() {
+                Some(negated) => self.push_stack(Rc::new(Object::Integer(negated))),
+                None => Err(format!("integer overflow: -({})", value)),
+            },
+            Object::Float(value) => self.push_stack(Rc::new(Object::Float(-value))),
             _ => Err(format!(
                 "unsupported type for negation: {}",
                 operand.get_type_str()
@@ -435,7 +624,7 @@ impl Vm {
     }
 
     fn build_hash(&self, start: usize, end: usize) -> Result<Object, VmError> {
-        let mut pairs = BTreeMap::new();
+        let mut pairs = HashPairs::new();
         for i in (start..end).step_by(2) {
             let key = self.stack[i].clone();
             match key.get_hash_key() {
@@ -461,6 +650,12 @@ impl Vm {
             (Object::Array(array), Object::Integer(integer)) => {
                 self.execute_array_index_expression(array, *integer as usize)
             }
+            (Object::Range { start, end, step }, Object::Integer(integer)) => {
+                self.push_stack(match range_get(*start, *end, *step, *integer as usize) {
+                    Some(value) => Rc::new(Object::Integer(value)),
+                    None => Rc::new(Object::Null),
+                })
+            }
             (Object::Hash(hash), index) => self.execute_hash_index_expression(hash, index),
             _ => Err(format!(
                 "index operator not supported: {}",
@@ -483,7 +678,7 @@ impl Vm {
 
     fn execute_hash_index_expression(
         &mut self,
-        hash: &BTreeMap<HashKey, HashPair>,
+        hash: &HashPairs,
         index: &Object,
     ) -> Result<(), VmError> {
         if let Some(hash_key) = index.get_hash_key() {
@@ -500,11 +695,27 @@ impl Vm {
     fn execute_call(&mut self, num_args: usize) -> Result<(), VmError> {
         match self.stack[self.sp - 1 - num_args].as_ref() {
             Object::Closure(closure) => self.call_closure(closure.clone(), num_args),
-            Object::BuiltinFn(builtin_fn) => self.call_builtin(*builtin_fn, num_args),
+            Object::BuiltinFn(builtin) => self.call_builtin(builtin, num_args),
+            Object::Function { .. } => self.call_function(num_args),
             _ => Err("calling non-function".to_string()),
         }
     }
 
+    fn call_back(&mut self, closure: &Closure, args: &[Rc<Object>]) -> Result<Rc<Object>, VmError> {
+        self.push_stack(Rc::new(Object::Closure(closure.clone())))?;
+        for arg in args.iter() {
+            self.push_stack(arg.clone())?;
+        }
+        self.call_closure(closure.clone(), args.len())?;
+        self.run()?;
+
+        // `exit` halts the VM before the closure returns.
+        match self.frames_idx {
+            1 => Ok(self.stack[self.sp - 1].clone()),
+            _ => Ok(self.last_popped()),
+        }
+    }
+
     fn call_closure(&mut self, closure: Closure, num_args: usize) -> Result<(), VmError> {
         if num_args != closure.function.num_parameters {
             return Err(format!(
@@ -520,15 +731,58 @@ impl Vm {
         Ok(())
     }
 
-    fn call_builtin(&mut self, builtin_fn: BuiltinFn, num_args: usize) -> Result<(), VmError> {
-        let args = &self.stack[self.sp - num_args..self.sp];
+    fn call_builtin(&mut self, builtin: &Builtin, num_args: usize) -> Result<(), VmError> {
+        let args = self.stack[self.sp - num_args..self.sp].to_vec();
+
+        let result = self.lending_state(|| builtin.call(&args));
+        // As in the evaluator, an error from a builtin stops the program unless a `try` catches it.
+        if let Object::Error(message) = result.as_ref() {
+            return Err(message.to_owned());
+        }
+        self.sp -= num_args + 1;
+
+        self.push_stack(result)
+    }
+
+    // Functions built by the evaluator, such as those `compose` returns, run on the evaluator.
+    fn call_function(&mut self, num_args: usize) -> Result<(), VmError> {
+        let function = self.stack[self.sp - 1 - num_args].clone();
+        let args = self.stack[self.sp - num_args..self.sp].to_vec();
 
-        let result = builtin_fn(args);
+        let result = match self.lending_state(|| apply_callback(&function, &args)) {
+            Ok(result) => result,
+            Err(EvalError::Error(message)) => return Err(message),
+            Err(EvalError::Exit(code)) => Rc::new(Object::Exit(code)),
+        };
         self.sp -= num_args + 1;
 
         self.push_stack(result)
     }
 
+    // Runs `call` with this VM's constants and globals lent out, so that closures passed to a
+    // builtin can be called back.
+    fn lending_state<T>(&mut self, call: impl FnOnce() -> T) -> T {
+        let state = (mem::take(&mut self.constants), mem::take(&mut self.globals));
+        let outer = LENT_STATE.with(|lent| lent.replace(Some(state)));
+
+        let result = call();
+
+        let state = LENT_STATE.with(|lent| lent.replace(outer));
+        (self.constants, self.globals) = state.expect("lent state is returned by each callback");
+        result
+    }
+
+    // A `return` inside a `try` body leaves the function without reaching OpEndTry.
+    fn drop_returned_handlers(&mut self) {
+        while self
+            .handlers
+            .last()
+            .is_some_and(|handler| handler.frames_idx > self.frames_idx)
+        {
+            self.handlers.pop();
+        }
+    }
+
     pub fn last_popped(&self) -> Rc<Object> {
         self.stack[self.sp].clone()
     }
@@ -588,3 +842,9 @@ impl Vm {
         }
     }
 }
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
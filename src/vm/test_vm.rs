@@ -1,10 +1,11 @@
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
-    use std::{collections::BTreeMap, rc::Rc};
+    use std::rc::Rc;
 
     use crate::{
         compiler::Compiler,
-        object::{HashPair, Object},
+        object::{HashPair, HashPairs, Object},
         parser::parse,
         vm::Vm,
     };
@@ -17,11 +18,13 @@ mod tests {
             .compile(&program)
             .expect("error occurred while compiling program");
 
+        // Errors stop the VM, so they are compared the way the evaluator reports them.
         let mut vm = Vm::from_bytecode(bytecode);
-        vm.run().expect("error occurred while running vm");
-
-        let stack_el = vm.last_popped();
-        assert_eq!(expected, stack_el.as_ref().clone());
+        let stack_el = match vm.run() {
+            Ok(()) => vm.last_popped().as_ref().clone(),
+            Err(message) => Object::Error(message),
+        };
+        assert_eq!(expected, stack_el);
     }
 
     #[test]
@@ -43,6 +46,8 @@ mod tests {
             "-10",
             "-50 + 100 + -50",
             "(5 + 10 * 2 + 15 / 3) * 2 - 10",
+            "2 ** 10",
+            "-2 ** 2",
         ];
         let expected_objs = vec![
             Object::Integer(1),
@@ -61,6 +66,54 @@ mod tests {
             Object::Integer(-10),
             Object::Integer(0),
             Object::Integer(50),
+            Object::Integer(1024),
+            Object::Integer(-4),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_float_arithmetic() {
+        let inputs = [
+            "1e3",
+            "2.5e-1 + 1",
+            "-0.5 * 4",
+            "1 / 8.0",
+            "2.0 ** 3",
+            "1.5 > 1",
+        ];
+        let expected_objs = [
+            Object::Float(1000.0),
+            Object::Float(1.25),
+            Object::Float(-2.0),
+            Object::Float(0.125),
+            Object::Float(8.0),
+            Object::Boolean(true),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_array_equality() {
+        let inputs = [
+            "[1, 2, 3] == [1, 2, 3]",
+            "[1, 2, 3] != [1, 2, 3]",
+            "[1, 2, 3] == [1, 2]",
+            "[1, [2, 3]] != [1, [2, 4]]",
+            "to_array(range(0, 100000)) == to_array(range(0, 100001))",
+        ];
+        let expected_objs = [
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(false),
         ];
 
         for (i, input) in inputs.iter().enumerate() {
@@ -132,6 +185,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chained_comparisons() {
+        let inputs = [
+            "1 < 2 < 3",
+            "1 < 20 < 10",
+            "10 > 5 > 1",
+            "1 < 5 > 3",
+            "let x = 7; 0 < x < 10 == true",
+            "1.5 < 2 < 2.5",
+            "2 < 1 < len(1)",
+            "let inside = fn(x) { 0 < x < 10 }; [inside(5), inside(15)]",
+        ];
+        let expected_objs = [
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Array(vec![
+                Rc::new(Object::Boolean(true)),
+                Rc::new(Object::Boolean(false)),
+            ]),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_do_expressions() {
+        let inputs = [
+            "let y = do { let t = 2; t * 3 }; y;",
+            "1; do { }",
+            "do { let t = 2; }",
+            "let f = fn() { let y = do { let t = 4; t + 1 }; y * 2 }; f();",
+        ];
+        let expected_objs = [
+            Object::Integer(6),
+            Object::Null,
+            Object::Null,
+            Object::Integer(10),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
     #[test]
     fn test_conditionals() {
         let inputs = [
@@ -222,7 +326,7 @@ mod tests {
     fn test_hash_literals() {
         let inputs = ["{}", "{1: 2, 3: 4, 5: 6}", "{1: 2 + 3, 4: 5 * 6}"];
 
-        let mut expected_map1 = BTreeMap::new();
+        let mut expected_map1 = HashPairs::new();
         let key1 = Object::Integer(1);
         expected_map1.insert(
             key1.get_hash_key()
@@ -251,7 +355,7 @@ mod tests {
             },
         );
 
-        let mut expected_map2 = BTreeMap::new();
+        let mut expected_map2 = HashPairs::new();
         let key1 = Object::Integer(1);
         expected_map2.insert(
             key1.get_hash_key()
@@ -272,7 +376,7 @@ mod tests {
         );
 
         let expected_objs = vec![
-            Object::Hash(BTreeMap::new()),
+            Object::Hash(HashPairs::new()),
             Object::Hash(expected_map1),
             Object::Hash(expected_map2),
         ];
@@ -295,6 +399,8 @@ mod tests {
             "{1: 1, 2: 2}[2]",
             "{1: 1}[0]",
             "{}[0]",
+            "range(0, 10, 2)[3]",
+            "range(3)[3]",
         ];
         let expected_objs = vec![
             Object::Integer(2),
@@ -307,6 +413,8 @@ mod tests {
             Object::Integer(2),
             Object::Null,
             Object::Null,
+            Object::Integer(6),
+            Object::Null,
         ];
 
         for (i, input) in inputs.iter().enumerate() {
@@ -441,6 +549,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_division_by_zero() {
+        let program =
+            parse("let f = fn(x) { 10 / x }; f(0);").expect("error occurred while parsing program");
+
+        let mut compiler = Compiler::new();
+        let bytecode = compiler
+            .compile(&program)
+            .expect("error occurred while compiling program");
+
+        let mut vm = Vm::from_bytecode(bytecode);
+        assert_eq!(vm.run(), Err("division by zero".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_wide_integers() {
+        test_running("2147483647 + 1", Object::Integer(2147483648));
+        test_running("3000000000 * 2", Object::Integer(6000000000));
+        test_running("2 ** 40", Object::Integer(1099511627776));
+        test_running("-(-2147483647 - 1)", Object::Integer(2147483648));
+        test_running("4294967296 > 2147483647", Object::Boolean(true));
+    }
+
+    #[test]
+    #[cfg(not(feature = "i64"))]
+    fn test_minus_overflow() {
+        test_running("let min = -2147483647 - 1; min", Object::Integer(i32::MIN));
+
+        let program =
+            parse("let min = -2147483647 - 1; -min").expect("error occurred while parsing program");
+
+        let mut compiler = Compiler::new();
+        let bytecode = compiler
+            .compile(&program)
+            .expect("error occurred while compiling program");
+
+        let mut vm = Vm::from_bytecode(bytecode);
+        assert_eq!(
+            vm.run(),
+            Err("integer overflow: -(-2147483648)".to_string())
+        );
+    }
+
     #[test]
     fn test_builtin_functions() {
         let inputs = [
@@ -490,6 +642,231 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pipe_expressions() {
+        test_running(
+            "let double = fn(x) { x * 2 }; 5 |> double",
+            Object::Integer(10),
+        );
+        test_running(
+            "let sub = fn(a, b) { a - b }; 10 |> sub(3) |> sub(2)",
+            Object::Integer(5),
+        );
+        test_running("[1, 2, 3] |> push(4) |> len", Object::Integer(4));
+    }
+
+    #[test]
+    fn test_partial_application() {
+        let inputs = [
+            "let sub = fn(a, b) { a - b }; let dec = sub(_, 1); dec(5);",
+            "let sub = fn(a, b) { a - b }; let from_ten = sub(10, _); from_ten(3);",
+            "let add = fn(a, b, c) { a + b + c }; let mid = add(1, _, _); mid(2, 3);",
+            "let append = push(_, 4); append([1]);",
+            "let sub = fn(a, b) { a - b }; let by = fn(n) { sub(_, n) }; by(2)(7);",
+            "let sub = fn(a, b) { a - b }; sub(sub(10, _)(1), _)(2);",
+            "let sub = fn(a, b) { a - b }; 10 |> sub(_, 4)",
+        ];
+        let expected_objs = [
+            Object::Integer(4),
+            Object::Integer(7),
+            Object::Integer(6),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(4)),
+            ]),
+            Object::Integer(5),
+            Object::Integer(7),
+            Object::Integer(6),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_match_expressions() {
+        let inputs = [
+            "match 2 { case 1 => \"one\", case 2 => \"two\", default => \"many\" }",
+            "match 7 { case 1 => \"one\", case 2 => \"two\", default => \"many\" }",
+            "match \"x\" { case \"x\" => 1, case \"y\" => 2 }",
+            "match 3 { case 1 => 1 }",
+            "let x = 4; match x * 2 { case x + 4 => true, default => false }",
+            "match [1, [2]] { case [1, [2]] => \"nested\", default => \"no\" }",
+            "match 15 { case n if n > 20 => \"big\", case n if n > 10 => \"medium\", default => \"small\" }",
+            "match 25 { case n if n > 20 => \"big\", case n if n > 10 => \"medium\", default => \"small\" }",
+            "match 5 { case n if n > 20 => \"big\", case n if n > 10 => \"medium\", default => \"small\" }",
+            "match 12 { case n if n > 10 => n * 2 }",
+            "let big = false; match 1 { case 1 if big => \"big one\", case 1 => \"one\" }",
+            "let n = 1; match 30 { case n if n > 20 => n }; n;",
+            "let limit = 5; match 3 { case limit => \"bound\", default => \"nope\" }",
+            "let limit = 5; match 3 { case limit => limit }",
+            "let limit = 5; match 3 { case n if n == limit => \"same\", default => \"different\" }",
+            "let sign = fn(x) { match x { case 0 => 0, case n if n > 0 => 1, default => -1 } }; [sign(0), sign(4), sign(-4)]",
+            "let f = fn(x) { match x { case n => fn() { n } } }; f(9)()",
+        ];
+        let expected_objs = [
+            Object::String("two".to_string()),
+            Object::String("many".to_string()),
+            Object::Integer(1),
+            Object::Null,
+            Object::Boolean(true),
+            Object::String("nested".to_string()),
+            Object::String("medium".to_string()),
+            Object::String("big".to_string()),
+            Object::String("small".to_string()),
+            Object::Integer(24),
+            Object::String("one".to_string()),
+            Object::Integer(1),
+            Object::String("bound".to_string()),
+            Object::Integer(3),
+            Object::String("different".to_string()),
+            Object::Array(vec![
+                Rc::new(Object::Integer(0)),
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(-1)),
+            ]),
+            Object::Integer(9),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_try_expressions() {
+        let inputs = [
+            "try { 1 / 0 } catch (e) { e }",
+            "try { 10 / 2 } catch (e) { e }",
+            "let f = fn(x) { 10 / x }; try { f(0); } catch (err) { \"caught: \" + err }",
+            "try { len(1) } catch (e) { 0 }",
+            "let e = 1; try { 1 / 0 } catch (e) { 2 }; e;",
+            "try { try { 1 / 0 } catch (e) { raise(\"again\") } } catch (e) { e }",
+            "let f = fn() { try { return 1; } catch (e) { 2 } }; f(); try { 1 / 0 } catch (e) { 3 }",
+            "let f = fn() { try { return 1; } catch (e) { 2 } }; f(); 1 / 0",
+            "[1, try { [2, 1 / 0] } catch (e) { 3 }, 4]",
+            "try { exit(4) } catch (e) { 1 }",
+        ];
+        let expected_objs = [
+            Object::String("division by zero".to_string()),
+            Object::Integer(5),
+            Object::String("caught: division by zero".to_string()),
+            Object::Integer(0),
+            Object::Integer(1),
+            Object::String("again".to_string()),
+            Object::Integer(3),
+            Object::Error("division by zero".to_string()),
+            Object::Array(vec![
+                Rc::new(Object::Integer(1)),
+                Rc::new(Object::Integer(3)),
+                Rc::new(Object::Integer(4)),
+            ]),
+            Object::Exit(4),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_raise() {
+        let inputs = [
+            "raise(\"boom\")",
+            "raise(\"boom\"); 5;",
+            "try { raise(\"boom\"); 5 } catch (e) { \"caught \" + e }",
+            "let check = fn(x) { if (x < 0) { raise(\"negative\") }; x }; try { check(-1) } catch (e) { e }",
+            "len(1); 5;",
+        ];
+        let expected_objs = [
+            Object::Error("boom".to_string()),
+            Object::Error("boom".to_string()),
+            Object::String("caught boom".to_string()),
+            Object::String("negative".to_string()),
+            Object::Error("argument to 'len' not supported, found INTEGER".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_callback_builtins() {
+        let int_array = |values: &[i64]| {
+            Object::Array(
+                values
+                    .iter()
+                    .map(|value| Rc::new(Object::Integer(*value as _)))
+                    .collect(),
+            )
+        };
+        let inputs = [
+            "let f = fn(a, b, c) { a * 100 + b * 10 + c }; apply(f, [1, 2, 3])",
+            "let inc = fn(x) { x + 1 }; let double = fn(x) { x * 2 }; compose(inc, double)(3)",
+            "let n = 10; repeat(3, fn(i) { n + i })",
+            "count([1, 2, 3, 4], fn(x) { x / 2 * 2 == x })",
+            "min_by([3, 1, 2], fn(x) { -x })",
+            "max_by([[1], [1, 2], []], len)",
+            "let groups = group_by([1, 2, 3, 4], fn(x) { x - x / 2 * 2 }); [groups[0], groups[1]]",
+            "sort_by([3, 1, 2], fn(x) { -x })",
+            "any([1, 2], fn(x) { x > 1 })",
+            "all([1, 2], fn(x) { x > 1 })",
+            "times(3, fn(i) { if (i == 2) { exit(i + 5) } }); 1",
+            "let scale = fn(k) { fn(x) { x * k } }; repeat(2, fn(i) { repeat(2, scale(i + 1)) })",
+            "try { repeat(2, fn(i) { 1 / i }) } catch (e) { e }",
+        ];
+        let expected_objs = [
+            Object::Integer(123),
+            Object::Integer(7),
+            int_array(&[10, 11, 12]),
+            Object::Integer(2),
+            Object::Integer(3),
+            int_array(&[1, 2]),
+            Object::Array(vec![
+                Rc::new(int_array(&[2, 4])),
+                Rc::new(int_array(&[1, 3])),
+            ]),
+            int_array(&[3, 2, 1]),
+            Object::Boolean(true),
+            Object::Boolean(false),
+            Object::Exit(7),
+            Object::Array(vec![
+                Rc::new(int_array(&[0, 1])),
+                Rc::new(int_array(&[0, 2])),
+            ]),
+            Object::String("division by zero".to_string()),
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
+
+    #[test]
+    fn test_eval_builtin() {
+        // The VM has no evaluator environment for `eval` to run in.
+        test_running(
+            "eval(\"3 * 4\")",
+            Object::Error("'eval' must be called directly".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_empty_programs() {
+        test_running("", Object::Null);
+        test_running("  \n\t ", Object::Null);
+        test_running("// nothing to see here", Object::Null);
+    }
+
+    #[test]
+    fn test_block_statements() {
+        test_running("{ let x = 1; x + 1 }", Object::Integer(2));
+        test_running("let x = 1; { x * 10 }", Object::Integer(10));
+        test_running("{ let x = 1; }", Object::Null);
+    }
+
     #[test]
     fn test_closures() {
         let inputs = [
@@ -539,4 +916,39 @@ mod tests {
             test_running(input, expected_objs[i].clone());
         }
     }
+
+    #[test]
+    fn test_const_statements() {
+        let inputs = [
+            "const one = 1; one",
+            "const one = 1; let two = one + one; two",
+        ];
+        let expected_objs = [Object::Integer(1), Object::Integer(2)];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+
+        let program =
+            parse("const one = 1; let one = 2;").expect("error occurred while parsing program");
+        let mut compiler = Compiler::new();
+        assert_eq!(
+            compiler.compile(&program).err(),
+            Some("cannot reassign constant: one".to_string())
+        );
+    }
+
+    #[test]
+    fn test_exit() {
+        let inputs = [
+            "exit(3); 5;",
+            "let f = fn() { exit(2); 1; }; f(); 3;",
+            "let f = fn(x) { if (x > 1) { exit(x); }; 0; }; f(1); f(5); 3;",
+        ];
+        let expected_objs = [Object::Exit(3), Object::Exit(2), Object::Exit(5)];
+
+        for (i, input) in inputs.iter().enumerate() {
+            test_running(input, expected_objs[i].clone());
+        }
+    }
 }